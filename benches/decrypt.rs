@@ -0,0 +1,43 @@
+//! Compare trying the keys of a multi-key `DOTENV_KEY` one after another with trying them
+//! concurrently by running with and without the `parallel` feature:
+//!
+//! ```text
+//! cargo bench --bench decrypt
+//! cargo bench --bench decrypt --features parallel
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dotenv_vault::Vault;
+
+const VAULT: &str = "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"";
+const KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+/// Four keys that cannot decrypt the vault followed by the one that can
+fn keys() -> String {
+    let mut keys: Vec<String> = (1..5)
+        .map(|i| {
+            format!(
+                "dotenv://:key_{}@dotenv.local/vault/.env.vault?environment=production",
+                i.to_string().repeat(64)
+            )
+        })
+        .collect();
+    keys.push(KEY.to_string());
+    keys.join(",")
+}
+
+fn decrypt_five_keys(c: &mut Criterion) {
+    let name = if cfg!(feature = "parallel") {
+        "decrypt_five_keys_parallel"
+    } else {
+        "decrypt_five_keys_sequential"
+    };
+    let vault = Vault::from_bytes(VAULT, &keys());
+
+    c.bench_function(name, |b| {
+        b.iter(|| black_box(vault.decrypt_entry().unwrap()))
+    });
+}
+
+criterion_group!(benches, decrypt_five_keys);
+criterion_main!(benches);
@@ -0,0 +1,139 @@
+//! Procedural macros for [dotenv-vault](https://crates.io/crates/dotenv-vault), available
+//! through its `macros` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+/// Adds a `from_dotenv_vault()` constructor to a struct that implements `Default`, which loads the
+/// *.env.vault* file with `dotenv_vault::dotenv_try()` and then populates each field from an
+/// environment variable
+///
+/// It is fine for neither a *.env.vault* nor a regular *.env* file to exist, but any other error,
+/// such as an invalid or mismatched *DOTENV_KEY*, panics with the error message.
+///
+/// Each field is read from the variable named by its `#[env(key = "...")]` attribute, or else its
+/// name in upper case, and parsed with `FromStr`. Fields marked `#[env(required)]` panic if the
+/// variable is not set; other fields keep their default value. Any field panics if its variable
+/// cannot be parsed.
+///
+/// # Examples
+/// ```no_run
+/// use dotenv_vault::dotenv_vault;
+///
+/// #[dotenv_vault]
+/// #[derive(Default)]
+/// struct Config {
+///     #[env(key = "DATABASE_URL", required)]
+///     database_url: String,
+///     #[env(key = "PORT")]
+///     port: u16,
+///     debug: bool,
+/// }
+///
+/// let config = Config::from_dotenv_vault();
+/// println!("listening on {}", config.port);
+/// ```
+#[proc_macro_attribute]
+pub fn dotenv_vault(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+
+    if !args.is_empty() {
+        return Error::new(Span::call_site(), "#[dotenv_vault] takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    match expand(&mut input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &mut DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &mut input.data {
+        Data::Struct(data) => match &mut data.fields {
+            Fields::Named(fields) => &mut fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "#[dotenv_vault] requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[dotenv_vault] can only be used on structs",
+            ))
+        }
+    };
+
+    let mut assignments = Vec::new();
+    for field in fields.iter_mut() {
+        let ident = field.ident.clone().expect("named field");
+        let mut key = LitStr::new(&ident.to_string().to_uppercase(), ident.span());
+        let mut required = false;
+
+        let mut result = Ok(());
+        field.attrs.retain(|attr| {
+            if !attr.path().is_ident("env") {
+                return true;
+            }
+
+            let parsed = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    key = meta.value()?.parse()?;
+                    Ok(())
+                } else if meta.path.is_ident("required") {
+                    required = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `key = \"...\"` or `required`"))
+                }
+            });
+            if let Err(err) = parsed {
+                result = Err(err);
+            }
+            false
+        });
+        result?;
+
+        let invalid = format!("{} is not valid", key.value());
+        assignments.push(if required {
+            let missing = format!("{} not set", key.value());
+            quote! {
+                config.#ident = ::std::env::var(#key).expect(#missing).parse().expect(#invalid);
+            }
+        } else {
+            quote! {
+                if let Ok(value) = ::std::env::var(#key) {
+                    config.#ident = value.parse().expect(#invalid);
+                }
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #input
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Load the *.env.vault* file and populate a new instance from the environment
+            pub fn from_dotenv_vault() -> Self {
+                if let ::core::option::Option::Some(::core::result::Result::Err(err)) =
+                    ::dotenv_vault::dotenv_try()
+                {
+                    ::core::panic!("{}", err);
+                }
+
+                let mut config = <Self as ::core::default::Default>::default();
+                #(#assignments)*
+                config
+            }
+        }
+    })
+}
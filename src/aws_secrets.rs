@@ -0,0 +1,90 @@
+use super::errors::{Error, Result};
+
+use aws_credential_types::provider::ProvideCredentials;
+use aws_sigv4::{
+    http_request::{sign, SignableBody, SignableRequest, SigningSettings},
+    sign::v4,
+};
+use std::{error, time::SystemTime};
+
+fn aws_error(err: impl Into<Box<dyn error::Error + Send + Sync>>) -> Error {
+    Error::AwsSecretsError(err.into())
+}
+
+/// Fetch the string value of a secret from AWS Secrets Manager, using the region, credentials
+/// and endpoint from the standard AWS environment variables and configuration files
+///
+/// The `GetSecretValue` request is signed with SigV4 and sent directly, which keeps the full
+/// Secrets Manager SDK out of the dependency tree.
+pub(crate) async fn fetch_async(name: &str) -> Result<String> {
+    let config = aws_config::load_from_env().await;
+    let region = config
+        .region()
+        .ok_or_else(|| aws_error("No AWS region configured"))?
+        .to_string();
+    let credentials = config
+        .credentials_provider()
+        .ok_or_else(|| aws_error("No AWS credentials configured"))?
+        .provide_credentials()
+        .await
+        .map_err(aws_error)?;
+    let endpoint = match config.endpoint_url() {
+        Some(endpoint) => endpoint.to_string(),
+        None => format!("https://secretsmanager.{}.amazonaws.com/", region),
+    };
+
+    let body = serde_json::json!({ "SecretId": name }).to_string();
+    let headers = [
+        ("content-type", "application/x-amz-json-1.1"),
+        ("x-amz-target", "secretsmanager.GetSecretValue"),
+    ];
+
+    let identity = credentials.into();
+    let params = v4::SigningParams::builder()
+        .identity(&identity)
+        .region(&region)
+        .name("secretsmanager")
+        .time(SystemTime::now())
+        .settings(SigningSettings::default())
+        .build()
+        .map_err(aws_error)?
+        .into();
+    let signable = SignableRequest::new(
+        "POST",
+        endpoint.as_str(),
+        headers.iter().copied(),
+        SignableBody::Bytes(body.as_bytes()),
+    )
+    .map_err(aws_error)?;
+    let (instructions, _) = sign(signable, &params).map_err(aws_error)?.into_parts();
+
+    let mut request = reqwest::Client::new().post(&endpoint).body(body.clone());
+    for (name, value) in headers.iter().copied().chain(instructions.headers()) {
+        request = request.header(name, value);
+    }
+
+    let response: serde_json::Value = request
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(aws_error)?
+        .json()
+        .await
+        .map_err(aws_error)?;
+
+    response["SecretString"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| aws_error(format!("Secret {} has no string value", name)))
+}
+
+/// Fetch the string value of a secret like [`fetch_async`], blocking on a new single-threaded
+/// runtime
+///
+/// This must not be called from within an async runtime.
+pub(crate) fn fetch(name: &str) -> Result<String> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(fetch_async(name))
+}
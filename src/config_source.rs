@@ -0,0 +1,67 @@
+use super::vault::Vault;
+
+use config::{ConfigError, Map, Source, Value, ValueKind};
+use std::fmt;
+
+/// A [`Source`] that provides the variables of the *.env.vault* file, or a regular *.env* file if
+/// the key or vault cannot be found, as a configuration layer.
+///
+/// Variable names are lowercased to match the behaviour of config's `Environment` source.
+///
+/// # Examples
+/// ```no_run
+/// use config::Config;
+/// use dotenv_vault::VaultSource;
+///
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let config = Config::builder().add_source(VaultSource::default()).build()?;
+///     let s3_bucket = config.get_string("s3_bucket")?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct VaultSource {
+    /// Vault to source values from
+    vault: Vault,
+}
+
+impl VaultSource {
+    /// Create a source that decrypts the *.env.vault* file in the current directory with the given
+    /// dotenv key instead of the *DOTENV_KEY* environment variable
+    pub fn from_key(key: &str) -> Self {
+        Self {
+            vault: Vault::from_key(key),
+        }
+    }
+}
+
+impl fmt::Debug for VaultSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The vault holds the dotenv key, so keep it out of debug output
+        f.debug_struct("VaultSource").finish_non_exhaustive()
+    }
+}
+
+impl Source for VaultSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let origin = String::from("dotenv-vault");
+        let map = self
+            .vault
+            .to_map()
+            .map_err(|err| ConfigError::Foreign(Box::new(err)))?
+            .into_iter()
+            .map(|(key, value)| {
+                (
+                    key.to_lowercase(),
+                    Value::new(Some(&origin), ValueKind::String(value)),
+                )
+            })
+            .collect();
+
+        Ok(map)
+    }
+}
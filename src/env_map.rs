@@ -0,0 +1,191 @@
+use super::errors::Result;
+
+use std::{collections::HashMap, env, io, ops::Deref};
+
+/// Variables read from a *.env.vault* or *.env* file, without touching the process environment
+///
+/// Returned by [`Vault::into_env_map`](crate::Vault::into_env_map).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvMap(HashMap<String, String>);
+
+impl EnvMap {
+    /// Set the variables in the process environment, keeping the value of variables that are
+    /// already set like [`Vault::load`](crate::Vault::load)
+    pub fn apply(&self) -> Result<()> {
+        self.set_vars(false)
+    }
+
+    /// Set the variables in the process environment, overriding any existing values like
+    /// [`Vault::load_override`](crate::Vault::load_override)
+    pub fn apply_override(&self) -> Result<()> {
+        self.set_vars(true)
+    }
+
+    /// Combine two maps, taking the value from `other` for variables defined in both
+    pub fn merge(mut self, other: EnvMap) -> EnvMap {
+        self.0.extend(other.0);
+        self
+    }
+
+    /// Get a map with only the variables whose names start with `prefix`
+    pub fn filtered(&self, prefix: &str) -> EnvMap {
+        EnvMap(
+            self.0
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        )
+    }
+
+    /// Get a map without the variables named in `keys`
+    pub fn without(&self, keys: &[&str]) -> EnvMap {
+        EnvMap(
+            self.0
+                .iter()
+                .filter(|(key, _)| !keys.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        )
+    }
+
+    /// Get the underlying map
+    pub fn into_inner(self) -> HashMap<String, String> {
+        self.0
+    }
+
+    fn set_vars(&self, override_: bool) -> Result<()> {
+        // `env::set_var` panics on these names instead of returning an error
+        if let Some(key) = self
+            .0
+            .keys()
+            .find(|key| key.is_empty() || key.contains(['=', '\0']))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid environment variable name {:?}", key),
+            )
+            .into());
+        }
+
+        for (key, value) in &self.0 {
+            if override_ || env::var_os(key).is_none() {
+                env::set_var(key, value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Deref for EnvMap {
+    type Target = HashMap<String, String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<HashMap<String, String>> for EnvMap {
+    fn from(map: HashMap<String, String>) -> Self {
+        Self(map)
+    }
+}
+
+impl IntoIterator for EnvMap {
+    type Item = (String, String);
+    type IntoIter = std::collections::hash_map::IntoIter<String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a EnvMap {
+    type Item = (&'a String, &'a String);
+    type IntoIter = std::collections::hash_map::Iter<'a, String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Vault};
+    use serial_test::serial;
+
+    fn map(entries: &[(&str, &str)]) -> EnvMap {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>()
+            .into()
+    }
+
+    #[test]
+    fn into_env_map_ok() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        let env_map = vault.into_env_map().unwrap();
+        assert_eq!(env_map, map(&[("ALPHA", "omega"), ("BETA", "gamma")]));
+        assert_eq!(env_map.get("ALPHA").map(String::as_str), Some("omega"));
+    }
+
+    #[test]
+    fn merge_last_write_wins() {
+        let merged = map(&[("ALPHA", "omega"), ("BETA", "gamma")])
+            .merge(map(&[("BETA", "delta"), ("EPSILON", "zeta")]));
+
+        assert_eq!(
+            merged,
+            map(&[("ALPHA", "omega"), ("BETA", "delta"), ("EPSILON", "zeta")])
+        );
+    }
+
+    #[test]
+    fn filtered_and_without() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let env_map = vault.into_env_map().unwrap();
+
+        assert_eq!(env_map.filtered("ALPHA"), map(&[("ALPHA", "zeta")]));
+        assert_eq!(env_map.filtered("BETA"), EnvMap::default());
+        assert_eq!(env_map.without(&["ALPHA"]), EnvMap::default());
+        assert_eq!(env_map.without(&["BETA"]), env_map);
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn apply_keeps_existing() {
+        env::set_var("ENV_MAP_ALPHA", "existing");
+        env::remove_var("ENV_MAP_BETA");
+
+        let env_map = map(&[("ENV_MAP_ALPHA", "omega"), ("ENV_MAP_BETA", "gamma")]);
+        assert!(env_map.apply().is_ok());
+        assert_eq!(env::var("ENV_MAP_ALPHA").unwrap(), "existing");
+        assert_eq!(env::var("ENV_MAP_BETA").unwrap(), "gamma");
+
+        assert!(env_map.apply_override().is_ok());
+        assert_eq!(env::var("ENV_MAP_ALPHA").unwrap(), "omega");
+
+        env::remove_var("ENV_MAP_ALPHA");
+        env::remove_var("ENV_MAP_BETA");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn apply_invalid_name() {
+        env::remove_var("ENV_MAP_ALPHA");
+
+        let env_map = map(&[("ENV_MAP_ALPHA", "omega"), ("INVALID=NAME", "gamma")]);
+        assert!(matches!(env_map.apply(), Err(Error::IoError(_))));
+        assert!(env::var("ENV_MAP_ALPHA").is_err());
+    }
+}
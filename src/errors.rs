@@ -1,24 +1,444 @@
 use std::{error, fmt};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
 #[non_exhaustive]
 pub enum Error {
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::not_found_key),
+            help("Run 'npx dotenv-vault keys' to view your DOTENV_KEY")
+        )
+    )]
     KeyNotFound,
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::not_found_vault),
+            help("Run 'npx dotenv-vault build' to create a .env.vault file")
+        )
+    )]
     VaultNotFound,
+    #[cfg_attr(feature = "miette", diagnostic(code(dotenv_vault::dotenvy)))]
     DotenvyError(dotenvy::Error),
+    #[cfg_attr(feature = "miette", diagnostic(code(dotenv_vault::io)))]
+    IoError(std::io::Error),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::invalid_key),
+            help("A DOTENV_KEY looks like 'dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production'")
+        )
+    )]
     ParseError(url::ParseError),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::invalid_key),
+            help("A DOTENV_KEY looks like 'dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production'")
+        )
+    )]
     InvalidScheme,
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::invalid_key),
+            help("A DOTENV_KEY looks like 'dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production'")
+        )
+    )]
     MissingKey,
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::invalid_key),
+            help("A DOTENV_KEY looks like 'dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production'")
+        )
+    )]
     MissingEnvironment,
-    EnvironmentNotFound(String),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::not_found_environment),
+            help("Run 'npx dotenv-vault build' to include the environment")
+        )
+    )]
+    EnvironmentNotFound {
+        requested: String,
+        available: Vec<String>,
+    },
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::invalid_key),
+            help("Check that your DOTENV_KEY matches your .env.vault file")
+        )
+    )]
     InvalidKey,
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::invalid_key),
+            help("The key part of your DOTENV_KEY must be 64 hex characters")
+        )
+    )]
     HexError(hex::FromHexError),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::decryption_failed),
+            help("Check that your .env.vault file has not been modified")
+        )
+    )]
     DecodeError(base64::DecodeError),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::decryption_failed),
+            help("Check that your DOTENV_KEY matches your .env.vault file")
+        )
+    )]
     DecryptError(aes_gcm::Error),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::decryption_failed),
+            help("Check that your .env.vault file has not been truncated")
+        )
+    )]
+    InvalidCiphertext { actual_len: usize, min_len: usize },
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::nonce_reuse),
+            help("Run 'npx dotenv-vault build' to re-encrypt your .env.vault file")
+        )
+    )]
+    NonceReuse,
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::conflicting_variables),
+            help("Unset the conflicting variables or load with override")
+        )
+    )]
+    ConflictingVariables(Vec<String>),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::circular_reference),
+            help("Check the variables referenced by your .env.vault file")
+        )
+    )]
+    CircularReference(String),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::parse_value_failed),
+            help("Check the value of the variable in your .env.vault file")
+        )
+    )]
+    ParseValueError { key: String, source: String },
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::not_found_variable),
+            help("Add the variable to your .env.vault file or set it in the environment")
+        )
+    )]
+    RequiredVariableMissing(String),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::blocked_variable),
+            help("Remove the variable from your .env.vault file or load it with Vault::load")
+        )
+    )]
+    BlockedVariableName(String),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::unsupported_vault_version),
+            help("Upgrade dotenv-vault to read this .env.vault file")
+        )
+    )]
+    UnsupportedVaultVersion(u8),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::unsupported_compression),
+            help("Enable the `compress` feature of dotenv-vault to read zstd compressed vaults")
+        )
+    )]
+    UnsupportedCompression(String),
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::decryption_failed),
+            help("Entries for a DOTENV_KEY with kdf=pbkdf2 must start with a 16 byte salt")
+        )
+    )]
+    MissingSalt,
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::invalid_key),
+            help("Use kdf=pbkdf2 with a positive number of iterations, e.g. 'kdf=pbkdf2&iterations=600000'")
+        )
+    )]
+    InvalidKdf(String),
+    #[cfg(all(
+        any(feature = "remote", feature = "hashicorp-vault"),
+        not(target_arch = "wasm32")
+    ))]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::remote_fetch_failed),
+            help("Check that the vault URL and auth token are correct")
+        )
+    )]
+    RemoteError(reqwest::Error),
+    #[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::s3_fetch_failed),
+            help("Check the bucket, key, region and credentials, and that the IAM policy allows s3:GetObject")
+        )
+    )]
+    S3Error(Box<dyn error::Error + Send + Sync>),
+    #[cfg(all(feature = "aws-secrets", not(target_arch = "wasm32")))]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::aws_secret_fetch_failed),
+            help("Check the secret name, region and credentials, and that the IAM policy allows secretsmanager:GetSecretValue")
+        )
+    )]
+    AwsSecretsError(Box<dyn error::Error + Send + Sync>),
+    #[cfg(all(feature = "keychain", target_os = "macos"))]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::keychain_failed),
+            help("Check that the login Keychain is unlocked and allows dotenv-vault to access the dotenv-vault item")
+        )
+    )]
+    KeychainError(security_framework::base::Error),
+    #[cfg(all(feature = "wincred", target_os = "windows"))]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::wincred_failed),
+            help("Check that the dotenv-vault credential of this directory exists and is readable by the current user")
+        )
+    )]
+    WincredError(windows::core::Error),
+    #[cfg(all(feature = "secret-service", target_os = "linux"))]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::secret_service_failed),
+            help("Check that a Secret Service provider such as GNOME Keyring is running and its default collection is unlocked")
+        )
+    )]
+    SecretServiceError(secret_service::Error),
+    #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(dotenv_vault::load_timeout),
+            help("Check that the .env.vault file is reachable or raise the timeout")
+        )
+    )]
+    LoadTimeout(std::time::Duration),
+    #[cfg(feature = "serde")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(code(dotenv_vault::deserialization_failed))
+    )]
+    DeserializeError(envy::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+impl Error {
+    /// Returns how to fix the error, for errors that have an obvious remedy
+    ///
+    /// The hint is also appended to the error message.
+    pub fn hint(&self) -> Option<&'static str> {
+        match *self {
+            Error::KeyNotFound => Some("Run 'npx dotenv-vault keys' to view your DOTENV_KEY."),
+            Error::VaultNotFound => Some("Run 'npx dotenv-vault build' to create a .env.vault file."),
+            Error::ParseError(_) | Error::InvalidScheme => Some(
+                "A DOTENV_KEY has the format 'dotenv://:key_<KEY>@dotenv.org/vault/.env.vault?environment=<ENVIRONMENT>'.",
+            ),
+            Error::MissingKey => Some(
+                "Check that the key follows the ':' before the '@' of the DOTENV_KEY, as in 'dotenv://:key_<KEY>@dotenv.org/vault/.env.vault?environment=<ENVIRONMENT>'.",
+            ),
+            Error::MissingEnvironment => {
+                Some("Add the environment to the DOTENV_KEY, e.g. '?environment=production'.")
+            }
+            Error::EnvironmentNotFound { .. } => Some("Run 'npx dotenv-vault build' to include it."),
+            Error::HexError(_) => Some(
+                "The key may be corrupted, regenerate it with 'dotenv-vault generate-key' and re-encrypt the vault.",
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns the machine-readable code that prefixes the error message, e.g.
+    /// `"INVALID_DOTENV_KEY"`
+    pub fn error_code(&self) -> &'static str {
+        match *self {
+            Error::KeyNotFound => "NOT_FOUND_DOTENV_KEY",
+            Error::VaultNotFound => "NOT_FOUND_DOTENV_VAULT",
+            Error::DotenvyError(_) => "DOTENV_ERROR",
+            Error::IoError(_) => "IO_ERROR",
+            Error::ParseError(_) => "INVALID_DOTENV_KEY",
+            Error::InvalidScheme => "INVALID_DOTENV_KEY",
+            Error::MissingKey => "INVALID_DOTENV_KEY",
+            Error::MissingEnvironment => "INVALID_DOTENV_KEY",
+            Error::EnvironmentNotFound { .. } => "NOT_FOUND_DOTENV_ENVIRONMENT",
+            Error::InvalidKey => "INVALID_DOTENV_KEY",
+            Error::HexError(_) => "INVALID_DOTENV_KEY",
+            Error::DecodeError(_) => "DECRYPTION_FAILED",
+            Error::DecryptError(_) => "DECRYPTION_FAILED",
+            Error::InvalidCiphertext { .. } => "DECRYPTION_FAILED",
+            Error::NonceReuse => "NONCE_REUSE",
+            Error::ConflictingVariables(_) => "CONFLICTING_VARIABLES",
+            Error::CircularReference(_) => "CIRCULAR_REFERENCE",
+            Error::ParseValueError { .. } => "PARSE_VALUE_FAILED",
+            Error::RequiredVariableMissing(_) => "NOT_FOUND_VARIABLE",
+            Error::BlockedVariableName(_) => "BLOCKED_VARIABLE",
+            Error::UnsupportedVaultVersion(_) => "UNSUPPORTED_VAULT_VERSION",
+            Error::UnsupportedCompression(_) => "UNSUPPORTED_COMPRESSION",
+            Error::MissingSalt => "DECRYPTION_FAILED",
+            Error::InvalidKdf(_) => "INVALID_DOTENV_KEY",
+            #[cfg(all(
+                any(feature = "remote", feature = "hashicorp-vault"),
+                not(target_arch = "wasm32")
+            ))]
+            Error::RemoteError(_) => "REMOTE_FETCH_FAILED",
+            #[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+            Error::S3Error(_) => "S3_FETCH_FAILED",
+            #[cfg(all(feature = "aws-secrets", not(target_arch = "wasm32")))]
+            Error::AwsSecretsError(_) => "AWS_SECRET_FETCH_FAILED",
+            #[cfg(all(feature = "keychain", target_os = "macos"))]
+            Error::KeychainError(_) => "KEYCHAIN_FAILED",
+            #[cfg(all(feature = "wincred", target_os = "windows"))]
+            Error::WincredError(_) => "WINCRED_FAILED",
+            #[cfg(all(feature = "secret-service", target_os = "linux"))]
+            Error::SecretServiceError(_) => "SECRET_SERVICE_FAILED",
+            #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+            Error::LoadTimeout(_) => "LOAD_TIMEOUT",
+            #[cfg(feature = "serde")]
+            Error::DeserializeError(_) => "DESERIALIZATION_FAILED",
+        }
+    }
+
+    /// Returns the exit code a command line program should exit with for this error
+    ///
+    /// - `1` if something could not be found, such as the *DOTENV_KEY*, the *.env.vault* file,
+    ///   an environment or a required variable
+    /// - `2` if the vault cannot be decrypted, such as with the wrong key
+    /// - `3` if a dotenv key, the *.env.vault* file or its variables are invalid
+    /// - `4` if reading the vault failed, locally or from a remote source
+    pub fn into_exit_code(&self) -> i32 {
+        const NOT_FOUND: i32 = 1;
+        const AUTH: i32 = 2;
+        const CONFIG: i32 = 3;
+        const IO: i32 = 4;
+
+        let io_code = |error: &std::io::Error| match error.kind() {
+            std::io::ErrorKind::NotFound => NOT_FOUND,
+            _ => IO,
+        };
+
+        match *self {
+            Error::KeyNotFound => NOT_FOUND,
+            Error::VaultNotFound => NOT_FOUND,
+            Error::DotenvyError(dotenvy::Error::Io(ref error)) => io_code(error),
+            Error::DotenvyError(_) => CONFIG,
+            Error::IoError(ref error) => io_code(error),
+            Error::ParseError(_) => CONFIG,
+            Error::InvalidScheme => CONFIG,
+            Error::MissingKey => CONFIG,
+            Error::MissingEnvironment => CONFIG,
+            Error::EnvironmentNotFound { .. } => NOT_FOUND,
+            Error::InvalidKey => AUTH,
+            Error::HexError(_) => AUTH,
+            Error::DecodeError(_) => AUTH,
+            Error::DecryptError(_) => AUTH,
+            Error::InvalidCiphertext { .. } => AUTH,
+            Error::NonceReuse => AUTH,
+            Error::ConflictingVariables(_) => CONFIG,
+            Error::CircularReference(_) => CONFIG,
+            Error::ParseValueError { .. } => CONFIG,
+            Error::RequiredVariableMissing(_) => NOT_FOUND,
+            Error::BlockedVariableName(_) => CONFIG,
+            Error::UnsupportedVaultVersion(_) => CONFIG,
+            Error::UnsupportedCompression(_) => CONFIG,
+            Error::MissingSalt => AUTH,
+            Error::InvalidKdf(_) => CONFIG,
+            #[cfg(all(
+                any(feature = "remote", feature = "hashicorp-vault"),
+                not(target_arch = "wasm32")
+            ))]
+            Error::RemoteError(_) => IO,
+            #[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+            Error::S3Error(_) => IO,
+            #[cfg(all(feature = "aws-secrets", not(target_arch = "wasm32")))]
+            Error::AwsSecretsError(_) => IO,
+            #[cfg(all(feature = "keychain", target_os = "macos"))]
+            Error::KeychainError(_) => IO,
+            #[cfg(all(feature = "wincred", target_os = "windows"))]
+            Error::WincredError(_) => IO,
+            #[cfg(all(feature = "secret-service", target_os = "linux"))]
+            Error::SecretServiceError(_) => IO,
+            #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+            Error::LoadTimeout(_) => IO,
+            #[cfg(feature = "serde")]
+            Error::DeserializeError(_) => CONFIG,
+        }
+    }
+
+    /// Returns the error as a JSON object with `code`, `message`, and `details` fields, for use
+    /// in JSON error responses
+    ///
+    /// `code` is the same as [`Error::error_code`]. `details` is `null` except for
+    /// [`Error::EnvironmentNotFound`], where it holds the missing `environment` and the
+    /// `available` ones,
+    /// [`Error::ConflictingVariables`], where it holds the conflicting `variables`, and
+    /// [`Error::ParseValueError`], [`Error::RequiredVariableMissing`] and
+    /// [`Error::BlockedVariableName`], where it holds the variable's `key`.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        let details = match *self {
+            Error::EnvironmentNotFound {
+                ref requested,
+                ref available,
+            } => {
+                serde_json::json!({ "environment": requested, "available": available })
+            }
+            Error::ConflictingVariables(ref variables) => {
+                serde_json::json!({ "variables": variables })
+            }
+            Error::ParseValueError { ref key, .. } => serde_json::json!({ "key": key }),
+            Error::RequiredVariableMissing(ref key) => serde_json::json!({ "key": key }),
+            Error::BlockedVariableName(ref key) => serde_json::json!({ "key": key }),
+            _ => serde_json::Value::Null,
+        };
+
+        serde_json::json!({
+            "code": self.error_code(),
+            "message": self.to_string(),
+            "details": details,
+        })
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -32,6 +452,9 @@ impl fmt::Display for Error {
                 write!(f, "NOT_FOUND_DOTENV_VAULT: Cannot find vault file")
             }
             Error::DotenvyError(ref error) => error.fmt(f),
+            Error::IoError(ref error) => {
+                write!(f, "IO_ERROR: {}", error)
+            }
             Error::ParseError(_) => {
                 write!(f, "INVALID_DOTENV_KEY: Failed to parse url")
             }
@@ -44,8 +467,16 @@ impl fmt::Display for Error {
             Error::MissingEnvironment => {
                 write!(f, "INVALID_DOTENV_KEY: Missing environment part")
             }
-            Error::EnvironmentNotFound(ref environment) => {
-                write!(f, "NOT_FOUND_DOTENV_ENVIRONMENT: Cannot locate environment {} in your .env.vault file. Run 'npx dotenv-vault build' to include it.", environment)
+            Error::EnvironmentNotFound {
+                ref requested,
+                ref available,
+            } => {
+                let available = if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                };
+                write!(f, "NOT_FOUND_DOTENV_ENVIRONMENT: Cannot locate environment {} in your .env.vault file. Available environments: {}", requested, available)
             }
             Error::InvalidKey => {
                 write!(f, "INVALID_DOTENV_KEY: Key must be valid")
@@ -59,6 +490,151 @@ impl fmt::Display for Error {
             Error::DecryptError(_) => {
                 write!(f, "DECRYPTION_FAILED: Please check your DOTENV_KEY")
             }
+            Error::InvalidCiphertext {
+                actual_len,
+                min_len,
+            } => {
+                write!(
+                    f,
+                    "DECRYPTION_FAILED: Ciphertext is {} bytes but must be at least {} bytes",
+                    actual_len, min_len
+                )
+            }
+            Error::NonceReuse => {
+                write!(
+                    f,
+                    "NONCE_REUSE: Multiple vault entries are encrypted with the same nonce"
+                )
+            }
+            Error::ConflictingVariables(ref variables) => {
+                write!(
+                    f,
+                    "CONFLICTING_VARIABLES: Variables already set to a different value: {}",
+                    variables.join(", ")
+                )
+            }
+            Error::CircularReference(ref variable) => {
+                write!(
+                    f,
+                    "CIRCULAR_REFERENCE: Variable {} references itself through other variables",
+                    variable
+                )
+            }
+            Error::ParseValueError {
+                ref key,
+                ref source,
+            } => {
+                write!(
+                    f,
+                    "PARSE_VALUE_FAILED: Failed to parse variable {}: {}",
+                    key, source
+                )
+            }
+            Error::RequiredVariableMissing(ref key) => {
+                write!(
+                    f,
+                    "NOT_FOUND_VARIABLE: Cannot find required variable {}",
+                    key
+                )
+            }
+            Error::BlockedVariableName(ref key) => {
+                write!(
+                    f,
+                    "BLOCKED_VARIABLE: Refusing to load blocked variable {} from your .env.vault file",
+                    key
+                )
+            }
+            Error::UnsupportedVaultVersion(version) => {
+                write!(
+                    f,
+                    "UNSUPPORTED_VAULT_VERSION: Vault format version {} is not supported",
+                    version
+                )
+            }
+            Error::UnsupportedCompression(ref compression) => {
+                write!(
+                    f,
+                    "UNSUPPORTED_COMPRESSION: Vault compression {} is not supported",
+                    compression
+                )
+            }
+            Error::MissingSalt => {
+                write!(
+                    f,
+                    "DECRYPTION_FAILED: Ciphertext is too short to contain the key derivation salt"
+                )
+            }
+            Error::InvalidKdf(ref kdf) => {
+                write!(
+                    f,
+                    "INVALID_DOTENV_KEY: Key derivation {} is not supported",
+                    kdf
+                )
+            }
+            #[cfg(all(
+                any(feature = "remote", feature = "hashicorp-vault"),
+                not(target_arch = "wasm32")
+            ))]
+            Error::RemoteError(ref error) => {
+                write!(
+                    f,
+                    "REMOTE_FETCH_FAILED: Failed to fetch vault file: {}",
+                    error
+                )
+            }
+            #[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+            Error::S3Error(ref error) => {
+                write!(f, "S3_FETCH_FAILED: Failed to fetch vault file: {}", error)
+            }
+            #[cfg(all(feature = "aws-secrets", not(target_arch = "wasm32")))]
+            Error::AwsSecretsError(ref error) => {
+                write!(
+                    f,
+                    "AWS_SECRET_FETCH_FAILED: Failed to fetch secret: {}",
+                    error
+                )
+            }
+            #[cfg(all(feature = "keychain", target_os = "macos"))]
+            Error::KeychainError(ref error) => {
+                write!(
+                    f,
+                    "KEYCHAIN_FAILED: Failed to access the macOS Keychain: {}",
+                    error
+                )
+            }
+            #[cfg(all(feature = "wincred", target_os = "windows"))]
+            Error::WincredError(ref error) => {
+                write!(
+                    f,
+                    "WINCRED_FAILED: Failed to access the Windows Credential Manager: {}",
+                    error
+                )
+            }
+            #[cfg(all(feature = "secret-service", target_os = "linux"))]
+            Error::SecretServiceError(ref error) => {
+                write!(
+                    f,
+                    "SECRET_SERVICE_FAILED: Failed to access the Secret Service: {}",
+                    error
+                )
+            }
+            #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+            Error::LoadTimeout(duration) => {
+                write!(
+                    f,
+                    "LOAD_TIMEOUT: Loading the .env.vault file took longer than {:?}",
+                    duration
+                )
+            }
+            #[cfg(feature = "serde")]
+            Error::DeserializeError(ref error) => {
+                write!(f, "DESERIALIZATION_FAILED: {}", error)
+            }
+        }?;
+
+        match self.hint() {
+            Some(hint) => write!(f, ". {}", hint),
+            None => Ok(()),
         }
     }
 }
@@ -69,15 +645,46 @@ impl error::Error for Error {
             Error::KeyNotFound => None,
             Error::VaultNotFound => None,
             Error::DotenvyError(ref e) => Some(e),
+            Error::IoError(ref e) => Some(e),
             Error::ParseError(ref e) => Some(e),
             Error::InvalidScheme => None,
             Error::MissingKey => None,
             Error::MissingEnvironment => None,
-            Error::EnvironmentNotFound(_) => None,
+            Error::EnvironmentNotFound { .. } => None,
             Error::InvalidKey => None,
             Error::HexError(ref e) => Some(e),
             Error::DecodeError(ref e) => Some(e),
             Error::DecryptError(_) => None,
+            Error::InvalidCiphertext { .. } => None,
+            Error::NonceReuse => None,
+            Error::ConflictingVariables(_) => None,
+            Error::CircularReference(_) => None,
+            Error::ParseValueError { .. } => None,
+            Error::RequiredVariableMissing(_) => None,
+            Error::BlockedVariableName(_) => None,
+            Error::UnsupportedVaultVersion(_) => None,
+            Error::UnsupportedCompression(_) => None,
+            Error::MissingSalt => None,
+            Error::InvalidKdf(_) => None,
+            #[cfg(all(
+                any(feature = "remote", feature = "hashicorp-vault"),
+                not(target_arch = "wasm32")
+            ))]
+            Error::RemoteError(ref e) => Some(e),
+            #[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+            Error::S3Error(ref e) => Some(e.as_ref()),
+            #[cfg(all(feature = "aws-secrets", not(target_arch = "wasm32")))]
+            Error::AwsSecretsError(ref e) => Some(e.as_ref()),
+            #[cfg(all(feature = "keychain", target_os = "macos"))]
+            Error::KeychainError(ref e) => Some(e),
+            #[cfg(all(feature = "wincred", target_os = "windows"))]
+            Error::WincredError(ref e) => Some(e),
+            #[cfg(all(feature = "secret-service", target_os = "linux"))]
+            Error::SecretServiceError(ref e) => Some(e),
+            #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+            Error::LoadTimeout(_) => None,
+            #[cfg(feature = "serde")]
+            Error::DeserializeError(ref e) => Some(e),
         }
     }
 }
@@ -88,6 +695,12 @@ impl From<dotenvy::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Error {
+        Error::IoError(err)
+    }
+}
+
 impl From<hex::FromHexError> for Error {
     fn from(err: hex::FromHexError) -> Error {
         Error::HexError(err)
@@ -111,3 +724,348 @@ impl From<url::ParseError> for Error {
         Error::ParseError(err)
     }
 }
+
+#[cfg(all(
+    any(feature = "remote", feature = "hashicorp-vault"),
+    not(target_arch = "wasm32")
+))]
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Error {
+        Error::RemoteError(err)
+    }
+}
+
+#[cfg(all(feature = "secret-service", target_os = "linux"))]
+impl From<secret_service::Error> for Error {
+    fn from(err: secret_service::Error) -> Error {
+        Error::SecretServiceError(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<envy::Error> for Error {
+    fn from(err: envy::Error) -> Error {
+        Error::DeserializeError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn error_code_ok() {
+        let cases = [
+            (Error::KeyNotFound, "NOT_FOUND_DOTENV_KEY"),
+            (Error::VaultNotFound, "NOT_FOUND_DOTENV_VAULT"),
+            (
+                Error::DotenvyError(dotenvy::Error::LineParse("=".to_string(), 0)),
+                "DOTENV_ERROR",
+            ),
+            (
+                Error::IoError(std::io::Error::from(std::io::ErrorKind::PermissionDenied)),
+                "IO_ERROR",
+            ),
+            (
+                Error::ParseError(url::ParseError::EmptyHost),
+                "INVALID_DOTENV_KEY",
+            ),
+            (Error::InvalidScheme, "INVALID_DOTENV_KEY"),
+            (Error::MissingKey, "INVALID_DOTENV_KEY"),
+            (Error::MissingEnvironment, "INVALID_DOTENV_KEY"),
+            (
+                Error::EnvironmentNotFound {
+                    requested: "DOTENV_VAULT_STAGING".to_string(),
+                    available: vec!["production".to_string()],
+                },
+                "NOT_FOUND_DOTENV_ENVIRONMENT",
+            ),
+            (Error::InvalidKey, "INVALID_DOTENV_KEY"),
+            (
+                Error::HexError(hex::FromHexError::OddLength),
+                "INVALID_DOTENV_KEY",
+            ),
+            (
+                Error::DecodeError(base64::DecodeError::InvalidPadding),
+                "DECRYPTION_FAILED",
+            ),
+            (Error::DecryptError(aes_gcm::Error), "DECRYPTION_FAILED"),
+            (
+                Error::InvalidCiphertext {
+                    actual_len: 20,
+                    min_len: 28,
+                },
+                "DECRYPTION_FAILED",
+            ),
+            (Error::NonceReuse, "NONCE_REUSE"),
+            (
+                Error::ConflictingVariables(vec!["ALPHA".to_string()]),
+                "CONFLICTING_VARIABLES",
+            ),
+            (
+                Error::CircularReference("ALPHA".to_string()),
+                "CIRCULAR_REFERENCE",
+            ),
+            (
+                Error::ParseValueError {
+                    key: "PORT".to_string(),
+                    source: "invalid digit found in string".to_string(),
+                },
+                "PARSE_VALUE_FAILED",
+            ),
+            (
+                Error::RequiredVariableMissing("PORT".to_string()),
+                "NOT_FOUND_VARIABLE",
+            ),
+            (
+                Error::BlockedVariableName("LD_PRELOAD".to_string()),
+                "BLOCKED_VARIABLE",
+            ),
+            (
+                Error::UnsupportedVaultVersion(2),
+                "UNSUPPORTED_VAULT_VERSION",
+            ),
+            (
+                Error::UnsupportedCompression("gzip".to_string()),
+                "UNSUPPORTED_COMPRESSION",
+            ),
+            (Error::MissingSalt, "DECRYPTION_FAILED"),
+            (
+                Error::InvalidKdf("scrypt".to_string()),
+                "INVALID_DOTENV_KEY",
+            ),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(error.error_code(), code);
+        }
+    }
+
+    #[test]
+    fn into_exit_code_ok() {
+        let cases = [
+            (Error::KeyNotFound, 1),
+            (Error::VaultNotFound, 1),
+            (
+                Error::DotenvyError(dotenvy::Error::LineParse("=".to_string(), 0)),
+                3,
+            ),
+            (
+                Error::DotenvyError(dotenvy::Error::Io(std::io::Error::from(
+                    std::io::ErrorKind::NotFound,
+                ))),
+                1,
+            ),
+            (
+                Error::IoError(std::io::Error::from(std::io::ErrorKind::PermissionDenied)),
+                4,
+            ),
+            (
+                Error::IoError(std::io::Error::from(std::io::ErrorKind::NotFound)),
+                1,
+            ),
+            (Error::ParseError(url::ParseError::EmptyHost), 3),
+            (Error::InvalidScheme, 3),
+            (Error::MissingKey, 3),
+            (Error::MissingEnvironment, 3),
+            (
+                Error::EnvironmentNotFound {
+                    requested: "DOTENV_VAULT_STAGING".to_string(),
+                    available: Vec::new(),
+                },
+                1,
+            ),
+            (Error::InvalidKey, 2),
+            (Error::HexError(hex::FromHexError::OddLength), 2),
+            (Error::DecodeError(base64::DecodeError::InvalidPadding), 2),
+            (Error::DecryptError(aes_gcm::Error), 2),
+            (
+                Error::InvalidCiphertext {
+                    actual_len: 20,
+                    min_len: 28,
+                },
+                2,
+            ),
+            (Error::NonceReuse, 2),
+            (Error::ConflictingVariables(vec!["ALPHA".to_string()]), 3),
+            (Error::CircularReference("ALPHA".to_string()), 3),
+            (
+                Error::ParseValueError {
+                    key: "PORT".to_string(),
+                    source: "invalid digit found in string".to_string(),
+                },
+                3,
+            ),
+            (Error::RequiredVariableMissing("PORT".to_string()), 1),
+            (Error::BlockedVariableName("LD_PRELOAD".to_string()), 3),
+            (Error::UnsupportedVaultVersion(2), 3),
+            (Error::UnsupportedCompression("gzip".to_string()), 3),
+            (Error::MissingSalt, 2),
+            (Error::InvalidKdf("scrypt".to_string()), 3),
+            #[cfg(feature = "tokio")]
+            (Error::LoadTimeout(std::time::Duration::from_secs(5)), 4),
+        ];
+
+        for (error, code) in cases {
+            assert!((1..=4).contains(&error.into_exit_code()));
+            assert_eq!(error.into_exit_code(), code, "{}", error);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    fn to_json_ok() {
+        let json = Error::InvalidKey.to_json();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "INVALID_DOTENV_KEY",
+                "message": "INVALID_DOTENV_KEY: Key must be valid",
+                "details": null,
+            })
+        );
+        assert_eq!(json["code"], Error::InvalidKey.error_code());
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    fn to_json_environment_not_found() {
+        let error = Error::EnvironmentNotFound {
+            requested: "DOTENV_VAULT_STAGING".to_string(),
+            available: vec!["production".to_string()],
+        };
+        let json = error.to_json();
+
+        assert_eq!(json["code"], error.error_code());
+        assert_eq!(json["message"], error.to_string());
+        assert_eq!(
+            json["details"],
+            serde_json::json!({
+                "environment": "DOTENV_VAULT_STAGING",
+                "available": ["production"],
+            })
+        );
+    }
+
+    #[test]
+    fn io_error_source() {
+        use std::error::Error as _;
+
+        let error = Error::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert!(matches!(error, Error::IoError(_)));
+
+        let source = error.source().unwrap();
+        let io_error = source.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io_error.kind(), std::io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn dotenvy_error_preserved() {
+        let error = Error::from(dotenvy::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::NotFound,
+        )));
+        assert!(matches!(error, Error::DotenvyError(_)));
+    }
+
+    #[test]
+    fn error_code_prefixes_display() {
+        let error = Error::EnvironmentNotFound {
+            requested: "DOTENV_VAULT_STAGING".to_string(),
+            available: vec!["production".to_string()],
+        };
+        assert!(error
+            .to_string()
+            .starts_with(&format!("{}: ", error.error_code())));
+    }
+
+    #[test]
+    fn environment_not_found_display() {
+        let error = Error::EnvironmentNotFound {
+            requested: "DOTENV_VAULT_DEVELOPMENT".to_string(),
+            available: vec!["production".to_string(), "staging".to_string()],
+        };
+        assert!(error
+            .to_string()
+            .contains("Cannot locate environment DOTENV_VAULT_DEVELOPMENT in your .env.vault file. Available environments: production, staging."));
+
+        let error = Error::EnvironmentNotFound {
+            requested: "DOTENV_VAULT_DEVELOPMENT".to_string(),
+            available: Vec::new(),
+        };
+        assert!(error.to_string().contains("Available environments: none."));
+    }
+
+    #[test]
+    fn display_hints() {
+        let cases = [
+            (Error::KeyNotFound, "npx dotenv-vault keys"),
+            (Error::VaultNotFound, "npx dotenv-vault build"),
+            (
+                Error::ParseError(url::ParseError::RelativeUrlWithoutBase),
+                "dotenv://:key_<KEY>@",
+            ),
+            (Error::InvalidScheme, "dotenv://:key_<KEY>@"),
+            (Error::MissingKey, "dotenv://:key_<KEY>@"),
+            (Error::MissingEnvironment, "?environment=production"),
+            (
+                Error::EnvironmentNotFound {
+                    requested: "DOTENV_VAULT_STAGING".to_string(),
+                    available: Vec::new(),
+                },
+                "npx dotenv-vault build",
+            ),
+            (
+                Error::HexError(hex::FromHexError::OddLength),
+                "dotenv-vault generate-key",
+            ),
+        ];
+
+        for (error, expected) in cases {
+            let hint = error.hint().unwrap();
+            assert!(hint.contains(expected));
+            assert!(error.to_string().ends_with(&format!(". {}", hint)));
+        }
+
+        assert_eq!(Error::InvalidKey.hint(), None);
+        assert_eq!(
+            Error::InvalidKey.to_string(),
+            "INVALID_DOTENV_KEY: Key must be valid"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn error_code_load_timeout() {
+        let error = Error::LoadTimeout(std::time::Duration::from_secs(5));
+        assert_eq!(error.error_code(), "LOAD_TIMEOUT");
+        assert_eq!(
+            error.to_string(),
+            "LOAD_TIMEOUT: Loading the .env.vault file took longer than 5s"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn error_code_deserialize() {
+        let error = Error::DeserializeError(envy::Error::MissingValue("alpha"));
+        assert_eq!(error.error_code(), "DESERIALIZATION_FAILED");
+    }
+
+    #[test]
+    #[cfg(feature = "miette")]
+    fn miette_report_help() {
+        use miette::{NarratableReportHandler, Report};
+
+        let report = Report::new(Error::EnvironmentNotFound {
+            requested: "DOTENV_VAULT_STAGING".to_string(),
+            available: vec!["production".to_string()],
+        });
+        let mut rendered = String::new();
+        NarratableReportHandler::new()
+            .render_report(&mut rendered, report.as_ref())
+            .unwrap();
+
+        assert!(rendered.contains("Run 'npx dotenv-vault build' to include the environment"));
+        assert!(rendered.contains("dotenv_vault::not_found_environment"));
+    }
+}
@@ -0,0 +1,77 @@
+use super::vault::Vault;
+
+use figment::{
+    value::{Dict, Map, Value},
+    Error, Metadata, Profile, Provider,
+};
+
+/// A [`Provider`] that sources its values from the *.env.vault* file, or a regular *.env* file if
+/// the key or vault cannot be found.
+///
+/// Variable names are lowercased to match the behaviour of figment's `Env` provider, and values
+/// are parsed into the most specific [`Value`] possible.
+///
+/// # Examples
+/// ```no_run
+/// use dotenv_vault::VaultProvider;
+/// use figment::Figment;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     s3_bucket: String,
+/// }
+///
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let config: Config = Figment::new().merge(VaultProvider::default()).extract()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct VaultProvider {
+    /// Vault to source values from
+    vault: Vault,
+
+    /// Profile to emit values into
+    profile: Profile,
+}
+
+impl VaultProvider {
+    /// Create a provider that decrypts the *.env.vault* file in the current directory with the
+    /// given dotenv key instead of the *DOTENV_KEY* environment variable
+    pub fn from_key(key: &str) -> Self {
+        Self {
+            vault: Vault::from_key(key),
+            profile: Profile::Default,
+        }
+    }
+
+    /// Emit values into `profile` instead of the default profile
+    pub fn profile<P: Into<Profile>>(mut self, profile: P) -> Self {
+        self.profile = profile.into();
+        self
+    }
+}
+
+impl Provider for VaultProvider {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("dotenv-vault")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let dict = self
+            .vault
+            .to_map()
+            .map_err(|err| Error::from(err.to_string()))?
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value.parse::<Value>() {
+                    Ok(value) => value,
+                    Err(never) => match never {},
+                };
+                (key.to_lowercase(), value)
+            })
+            .collect();
+
+        Ok(self.profile.collect(dict))
+    }
+}
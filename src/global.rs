@@ -0,0 +1,76 @@
+use super::errors::Result;
+use super::vault::Vault;
+
+use std::{
+    collections::HashMap,
+    env,
+    sync::{PoisonError, RwLock},
+};
+
+/// Variables decrypted by [`init`], shared by the whole process
+///
+/// A `RwLock<Option<_>>` is used instead of a `OnceLock` so that [`reset`] can clear it.
+static CACHE: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+
+/// Decrypt the vault into the cache, unless a previous call already did
+pub fn init() -> Result<()> {
+    let mut cache = CACHE.write().unwrap_or_else(PoisonError::into_inner);
+    if cache.is_none() {
+        *cache = Some(Vault::new().to_map()?);
+    }
+
+    Ok(())
+}
+
+/// Decrypt the vault into the cache again
+///
+/// Returns `false` without decrypting anything if [`init`] has not been called.
+pub fn refresh() -> Result<bool> {
+    let mut cache = CACHE.write().unwrap_or_else(PoisonError::into_inner);
+    if cache.is_none() {
+        return Ok(false);
+    }
+
+    *cache = Some(Vault::new().to_map()?);
+    Ok(true)
+}
+
+/// Clear the cache so the next [`init`] decrypts the vault again
+pub fn reset() {
+    *CACHE.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Copy of the cached variables, if [`init`] has been called
+pub fn cached() -> Option<HashMap<String, String>> {
+    CACHE.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Look up a single cached variable
+///
+/// Returns `None` if [`init`] has not been called, and `Some(None)` if the variable is missing.
+pub fn get(key: &str) -> Option<Option<String>> {
+    CACHE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map(|map| map.get(key).cloned())
+}
+
+/// Set the cached variables in the environment
+///
+/// Returns `false` if [`init`] has not been called.
+pub fn apply(overwrite: bool) -> bool {
+    let cache = CACHE.read().unwrap_or_else(PoisonError::into_inner);
+    let map = match cache.as_ref() {
+        Some(map) => map,
+        None => return false,
+    };
+
+    for (key, value) in map {
+        if overwrite || env::var_os(key).is_none() {
+            env::set_var(key, value);
+        }
+    }
+
+    true
+}
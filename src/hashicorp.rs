@@ -0,0 +1,26 @@
+use super::errors::{Error, Result};
+
+use reqwest::blocking::Client;
+
+/// Fetch the `DOTENV_KEY` field of a secret from a HashiCorp Vault KV v2 store
+///
+/// `path` is the API path of the secret below `/v1/`, e.g. `secret/data/my-app`.
+pub(crate) fn fetch_key(addr: &str, token: &str, path: &str) -> Result<String> {
+    let url = format!(
+        "{}/v1/{}",
+        addr.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+
+    let response: serde_json::Value = Client::new()
+        .get(url)
+        .header("X-Vault-Token", token)
+        .send()?
+        .error_for_status()?
+        .json()?;
+
+    response["data"]["data"]["DOTENV_KEY"]
+        .as_str()
+        .map(|key| key.trim().to_string())
+        .ok_or(Error::KeyNotFound)
+}
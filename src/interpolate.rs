@@ -0,0 +1,215 @@
+use super::errors::{Error, Result};
+
+use std::{collections::HashMap, env};
+
+/// Expand `${VAR}` and `$VAR` references in decrypted *.env* contents
+///
+/// References resolve to the environment first, then to the other variables defined in
+/// `contents` regardless of their order. A variable referencing itself, as in
+/// `PATH=$PATH:/extra`, only resolves to the environment. References inside single quotes are
+/// left untouched, as are references that cannot be resolved.
+pub(crate) fn interpolate(contents: &str) -> Result<String> {
+    let mut resolver = Resolver {
+        definitions: contents.lines().filter_map(definition).collect(),
+        stack: Vec::new(),
+    };
+
+    let mut lines = Vec::new();
+    for line in contents.lines() {
+        match definition(line) {
+            Some((name, value)) => {
+                let prefix = &line[..line.len() - value.len()];
+                lines.push(format!("{}{}", prefix, resolver.expand(name, value)?));
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+
+    Ok(lines.join("\n"))
+}
+
+/// Split a `NAME=value` line into its name and raw value
+fn definition(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start();
+    let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+    let (name, value) = line.split_once('=')?;
+    let name = name.trim_end();
+
+    if name.is_empty() || !name.chars().all(|c| is_name_char(c) || c == '.') {
+        return None;
+    }
+
+    Some((name, value))
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Escape a resolved value so dotenvy reads it back literally, both unquoted and in double quotes
+pub(crate) fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '"' | '\'' | '$' | ' ' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct Resolver<'a> {
+    definitions: HashMap<&'a str, &'a str>,
+    stack: Vec<&'a str>,
+}
+
+impl<'a> Resolver<'a> {
+    /// Expand the references in the raw `value` of the variable `name`
+    fn expand(&mut self, name: &'a str, value: &'a str) -> Result<String> {
+        self.stack.push(name);
+
+        let mut output = String::with_capacity(value.len());
+        let mut strong_quote = false;
+        let mut weak_quote = false;
+        let mut position = 0;
+
+        while let Some(c) = value[position..].chars().next() {
+            position += c.len_utf8();
+
+            match c {
+                '\'' if !weak_quote => strong_quote = !strong_quote,
+                '"' if !strong_quote => weak_quote = !weak_quote,
+                '\\' if !strong_quote => {
+                    output.push(c);
+                    if let Some(escaped) = value[position..].chars().next() {
+                        position += escaped.len_utf8();
+                        output.push(escaped);
+                    }
+                    continue;
+                }
+                '$' if !strong_quote => {
+                    let rest = &value[position..];
+                    let (reference, length) = match rest.strip_prefix('{') {
+                        Some(block) => match block.find('}') {
+                            Some(end) => (&block[..end], end + 2),
+                            None => ("", 0),
+                        },
+                        None => {
+                            let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+                            (&rest[..end], end)
+                        }
+                    };
+
+                    if !reference.is_empty() {
+                        if let Some(resolved) = self.resolve(reference)? {
+                            output.push_str(&escape(&resolved));
+                            position += length;
+                            continue;
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            output.push(c);
+        }
+
+        self.stack.pop();
+        Ok(output)
+    }
+
+    /// Resolve a referenced variable from the environment or the other definitions
+    fn resolve(&mut self, name: &'a str) -> Result<Option<String>> {
+        if let Ok(value) = env::var(name) {
+            return Ok(Some(value));
+        }
+
+        if self.stack.last() == Some(&name) {
+            return Ok(None);
+        }
+
+        let raw = match self.definitions.get(name) {
+            Some(raw) => *raw,
+            None => return Ok(None),
+        };
+
+        if self.stack.contains(&name) {
+            return Err(Error::CircularReference(name.to_string()));
+        }
+
+        let expanded = self.expand(name, raw)?;
+        let value = dotenvy::from_read_iter(format!("{}={}", name, expanded).as_bytes())
+            .next()
+            .transpose()?
+            .map(|(_, value)| value);
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn parse(contents: &str) -> HashMap<String, String> {
+        dotenvy::from_read_iter(interpolate(contents).unwrap().as_bytes())
+            .collect::<dotenvy::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn interpolate_self_reference() {
+        env::set_var("INTERPOLATE_PATH", "/usr/bin");
+
+        let map = parse("INTERPOLATE_PATH=$INTERPOLATE_PATH:/extra");
+        assert_eq!(map["INTERPOLATE_PATH"], "/usr/bin:/extra");
+
+        env::remove_var("INTERPOLATE_PATH");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn interpolate_unset_self_reference() {
+        let map = parse("INTERPOLATE_PATH=${INTERPOLATE_PATH}:/extra");
+        assert_eq!(map["INTERPOLATE_PATH"], ":/extra");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn interpolate_forward_reference() {
+        let map = parse("URL=\"http://${HOST}:8080\"\nHOST=localhost");
+        assert_eq!(map["URL"], "http://localhost:8080");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn interpolate_escapes_values() {
+        env::set_var("INTERPOLATE_QUOTED", "he said \"hi\" to $USER");
+
+        let map = parse("ALPHA=\"${INTERPOLATE_QUOTED}\"\nBETA=$INTERPOLATE_QUOTED");
+        assert_eq!(map["ALPHA"], "he said \"hi\" to $USER");
+        assert_eq!(map["BETA"], "he said \"hi\" to $USER");
+
+        env::remove_var("INTERPOLATE_QUOTED");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn interpolate_single_quotes() {
+        let map = parse("HOST=localhost\nURL='${HOST}'");
+        assert_eq!(map["URL"], "${HOST}");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn interpolate_circular_reference() {
+        let result = interpolate("ALPHA=${BETA}\nBETA=${ALPHA}");
+        assert!(matches!(result, Err(Error::CircularReference(ref name)) if name == "ALPHA"));
+    }
+}
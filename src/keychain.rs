@@ -0,0 +1,34 @@
+use super::errors::{Error, Result};
+
+use security_framework::passwords::{get_generic_password, set_generic_password};
+use std::env;
+
+/// Service name of the Keychain items holding dotenv keys
+const SERVICE: &str = "dotenv-vault";
+
+/// `errSecItemNotFound`, returned when there is no item for the service and account
+const ITEM_NOT_FOUND: i32 = -25300;
+
+/// Account name of the Keychain item for the current directory
+fn account() -> Result<String> {
+    Ok(env::current_dir()?.display().to_string())
+}
+
+/// Read the dotenv key of the current directory from the macOS Keychain
+///
+/// Returns `None` if there is no Keychain item for the current directory.
+pub(crate) fn read_key() -> Result<Option<String>> {
+    match get_generic_password(SERVICE, &account()?) {
+        Ok(key) => Ok(
+            Some(String::from_utf8_lossy(&key).trim().to_string()).filter(|key| !key.is_empty())
+        ),
+        Err(err) if err.code() == ITEM_NOT_FOUND => Ok(None),
+        Err(err) => Err(Error::KeychainError(err)),
+    }
+}
+
+/// Store the dotenv key of the current directory in the macOS Keychain, replacing any existing
+/// one
+pub(crate) fn save_key(key: &str) -> Result<()> {
+    set_generic_password(SERVICE, &account()?, key.trim().as_bytes()).map_err(Error::KeychainError)
+}
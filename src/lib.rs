@@ -1,15 +1,134 @@
 //! Extends the [dotenvy](https://crates.io/crates/dotenvy) crate with *.env.vault* file support.
 //! The extended standard lets you load encrypted secrets from your *.env.vault* file in production (and other) environments.
 
+// Without a file system most of the vault lookup machinery goes unused on wasm32
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
+#[cfg(all(feature = "aws-secrets", not(target_arch = "wasm32")))]
+mod aws_secrets;
+#[cfg(feature = "config-rs")]
+mod config_source;
+mod env_map;
 mod errors;
+#[cfg(feature = "figment")]
+mod figment_provider;
+mod global;
+#[cfg(all(feature = "hashicorp-vault", not(target_arch = "wasm32")))]
+mod hashicorp;
+mod interpolate;
+#[cfg(all(feature = "keychain", target_os = "macos"))]
+mod keychain;
+#[cfg(all(feature = "secret-service", target_os = "linux"))]
+mod libsecret;
 mod log;
+#[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+mod remote;
+#[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+mod s3;
+#[cfg(any(feature = "opentelemetry", feature = "metrics"))]
+mod telemetry;
+#[cfg(all(feature = "test-utils", not(target_arch = "wasm32")))]
+pub mod test_utils;
 mod vault;
+mod vault_builder;
+mod vault_config;
+#[cfg(all(feature = "wincred", target_os = "windows"))]
+mod wincred;
 
+#[cfg(feature = "config-rs")]
+pub use config_source::VaultSource;
+#[cfg(feature = "macros")]
+pub use dotenv_vault_macros::dotenv_vault;
 pub use dotenvy;
+pub use env_map::EnvMap;
 pub use errors::Error;
+#[cfg(feature = "figment")]
+pub use figment_provider::VaultProvider;
+pub use log::LogLevel;
+pub use vault::{
+    AccessRecord, PreflightReport, Vault, VaultDiff, VaultIter, VaultStats, DEFAULT_BLOCKLIST,
+};
+pub use vault_builder::VaultBuilder;
+#[cfg(feature = "aws-secrets")]
+pub use vault_config::AwsSecretMode;
+pub use vault_config::{KeyOrder, VaultConfig};
 
 use errors::Result;
-use vault::Vault;
+use std::{collections::HashMap, path::Path};
+
+/// Reads a required environment variable and parses it into the given type, panicking with the
+/// variable name if it is missing or cannot be parsed.
+///
+/// Call this after loading the vault, e.g. with [`dotenv`].
+///
+/// # Examples
+/// ```
+/// std::env::set_var("PORT", "8080");
+///
+/// let port = dotenv_vault::require_env!(PORT: u16);
+/// assert_eq!(port, 8080);
+/// ```
+///
+/// A missing variable panics with its name:
+/// ```should_panic
+/// std::env::remove_var("S3_BUCKET");
+///
+/// let s3_bucket = dotenv_vault::require_env!(S3_BUCKET: String);
+/// ```
+#[macro_export]
+macro_rules! require_env {
+    ($key:ident : $ty:ty) => {
+        match ::std::env::var(::std::stringify!($key)) {
+            ::std::result::Result::Ok(value) => match value.parse::<$ty>() {
+                ::std::result::Result::Ok(value) => value,
+                ::std::result::Result::Err(err) => ::std::panic!(
+                    "Failed to parse environment variable {} as {}: {:?}",
+                    ::std::stringify!($key),
+                    ::std::stringify!($ty),
+                    err
+                ),
+            },
+            ::std::result::Result::Err(err) => ::std::panic!(
+                "Failed to read required environment variable {}: {}",
+                ::std::stringify!($key),
+                err
+            ),
+        }
+    };
+}
+
+/// Reads an optional environment variable and parses it into the given type, returning `None`
+/// if it is not set and panicking with the variable name if it cannot be parsed.
+///
+/// Call this after loading the vault, e.g. with [`dotenv`].
+///
+/// # Examples
+/// ```
+/// std::env::set_var("WORKERS", "4");
+/// std::env::remove_var("TIMEOUT");
+///
+/// let workers = dotenv_vault::optional_env!(WORKERS: usize);
+/// let timeout = dotenv_vault::optional_env!(TIMEOUT: u64);
+/// assert_eq!(workers, Some(4));
+/// assert_eq!(timeout, None);
+/// ```
+#[macro_export]
+macro_rules! optional_env {
+    ($key:ident : $ty:ty) => {
+        match ::std::env::var(::std::stringify!($key)) {
+            ::std::result::Result::Ok(value) => match value.parse::<$ty>() {
+                ::std::result::Result::Ok(value) => ::std::option::Option::Some(value),
+                ::std::result::Result::Err(err) => ::std::panic!(
+                    "Failed to parse environment variable {} as {}: {:?}",
+                    ::std::stringify!($key),
+                    ::std::stringify!($ty),
+                    err
+                ),
+            },
+            ::std::result::Result::Err(_) => ::std::option::Option::None,
+        }
+    };
+}
 
 /// Loads the *.env.vault* file from [`env::current_dir`](std::env::current_dir) using the *DOTENV_KEY* environment
 /// variable.
@@ -27,6 +146,9 @@ use vault::Vault;
 ///
 /// An error will be returned if the file is not found.
 ///
+/// If [`init`] has been called, the cached variables are loaded instead of decrypting the vault
+/// again.
+///
 /// # Examples
 /// ```no_run
 /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
@@ -35,6 +157,10 @@ use vault::Vault;
 /// }
 /// ```
 pub fn dotenv() -> Result<()> {
+    if global::apply(false) {
+        return Ok(());
+    }
+
     Vault::new().load()
 }
 
@@ -50,6 +176,9 @@ pub fn dotenv() -> Result<()> {
 /// or if you want to be able to override environment variables on the command line,
 /// then use [`dotenv`] instead.
 ///
+/// If [`init`] has been called, the cached variables are loaded instead of decrypting the vault
+/// again.
+///
 /// # Examples
 /// ```no_run
 /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
@@ -58,15 +187,402 @@ pub fn dotenv() -> Result<()> {
 /// }
 /// ```
 pub fn dotenv_override() -> Result<()> {
+    if global::apply(true) {
+        return Ok(());
+    }
+
     Vault::new().load_override()
 }
 
+/// Loads all variables into the environment like [`dotenv`], refusing to set any variable in
+/// [`DEFAULT_BLOCKLIST`].
+///
+/// If the vault contains a blocked variable such as `LD_PRELOAD`,
+/// [`Error::BlockedVariableName`] is returned and no variable is set.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     dotenv_vault::dotenv_guarded()?;
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_guarded() -> Result<()> {
+    Vault::new().load_into_process_env_guarded(DEFAULT_BLOCKLIST)
+}
+
+/// Reads and decrypts the *.env.vault* file again, or the regular *.env* file it falls back to,
+/// overriding any existing environment variables of the same name.
+///
+/// If [`init`] has been called, the cached variables are refreshed as well.
+///
+/// Reloading is inherently racy: other threads may observe a mix of old and new values while
+/// variables are being set, so callers should synchronize access externally.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     dotenv_vault::dotenv()?;
+///
+///     // later, e.g. on SIGHUP
+///     dotenv_vault::dotenv_reload()?;
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_reload() -> Result<()> {
+    if global::refresh()? {
+        global::apply(true);
+        return Ok(());
+    }
+
+    Vault::new().reload()
+}
+
+/// Decrypts the *.env.vault* file, or reads a regular *.env* file if the key or vault cannot be
+/// found, once for the whole process.
+///
+/// Later calls to [`dotenv`], [`dotenv_override`], [`dotenv_get`] and [`dotenv_to_map`] use the
+/// cached variables instead of decrypting the vault again, and later calls to `init` do nothing.
+///
+/// This is intended to be called once, early, by binary crates. Libraries should keep using
+/// [`dotenv`], which remains the low-level API.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     dotenv_vault::init()?;
+///     dotenv_vault::dotenv()?;
+///     Ok(())
+/// }
+/// ```
+pub fn init() -> Result<()> {
+    global::init()
+}
+
+/// Clears the variables cached by [`init`].
+///
+/// This is mostly useful in tests that need to load a different vault.
+pub fn reset_global() {
+    global::reset()
+}
+
+/// Gets a single variable from the *.env.vault* file, or a regular *.env* file if the key or vault
+/// cannot be found, without modifying the environment.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let s3_bucket = dotenv_vault::dotenv_get("S3_BUCKET")?;
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_get(key: &str) -> Result<Option<String>> {
+    if let Some(value) = global::get(key) {
+        return Ok(value);
+    }
+
+    Vault::new().get(key)
+}
+
+/// Gets a single variable like [`dotenv_get`], falling back to the environment variable of the
+/// same name if the *.env.vault* file does not define it.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let s3_bucket = dotenv_vault::dotenv_get_or_env("S3_BUCKET")?;
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_get_or_env(key: &str) -> Result<Option<String>> {
+    match dotenv_get(key)? {
+        Some(value) => Ok(Some(value)),
+        None => Ok(std::env::var(key).ok()),
+    }
+}
+
+/// Reads all variables from the *.env.vault* file, or a regular *.env* file if the key or vault
+/// cannot be found, into a map without modifying the environment.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let map = dotenv_vault::dotenv_to_map()?;
+///     println!("{:?}", map.get("S3_BUCKET"));
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_to_map() -> Result<HashMap<String, String>> {
+    if let Some(map) = global::cached() {
+        return Ok(map);
+    }
+
+    Vault::new().to_map()
+}
+
+/// Reads a single variable from the *.env.vault* file, or a regular *.env* file if the key or
+/// vault cannot be found, parsed into `T` without modifying the environment.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let port: u16 = dotenv_vault::dotenv_get_typed("PORT")?;
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_get_typed<T>(key: &str) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    vault::parse_value(key, dotenv_get(key)?)
+}
+
+/// Loads the *.env.vault* file, or a regular *.env* file if the key or vault cannot be found, like
+/// [`dotenv`], then reads the required variable `key` from the environment.
+///
+/// A missing variable is reported as [`Error::RequiredVariableMissing`].
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let s3_bucket = dotenv_vault::dotenv_require("S3_BUCKET")?;
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_require(key: &str) -> Result<String> {
+    dotenv()?;
+    std::env::var(key).map_err(|_| Error::RequiredVariableMissing(key.to_string()))
+}
+
+/// Loads the *.env.vault* file, or a regular *.env* file if the key or vault cannot be found, like
+/// [`dotenv`], then reads the required variable `key` from the environment parsed into `T`.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let port: u16 = dotenv_vault::dotenv_require_typed("PORT")?;
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_require_typed<T>(key: &str) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    vault::parse_value(key, Some(dotenv_require(key)?))
+}
+
+/// Counts the variables in the *.env.vault* file, or a regular *.env* file if the key or vault
+/// cannot be found, without modifying the environment.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     println!("{} variables", dotenv_vault::dotenv_len()?);
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_len() -> Result<usize> {
+    Ok(dotenv_to_map()?.len())
+}
+
+/// Checks whether the *.env.vault* file, or a regular *.env* file if the key or vault cannot be
+/// found, has no variables, without modifying the environment.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     if dotenv_vault::dotenv_is_empty()? {
+///         println!("No variables to load");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_is_empty() -> Result<bool> {
+    Ok(dotenv_len()? == 0)
+}
+
+/// Reads all variables from the *.env.vault* file, or a regular *.env* file if the key or vault
+/// cannot be found, into `map` without modifying the environment. Existing entries in `map` are
+/// kept.
+///
+/// # Examples
+/// ```no_run
+/// use std::collections::HashMap;
+///
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let mut map = HashMap::new();
+///     dotenv_vault::dotenv_into(&mut map)?;
+///     println!("{:?}", map.get("S3_BUCKET"));
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_into(map: &mut HashMap<String, String>) -> Result<()> {
+    if let Some(cached) = global::cached() {
+        for (key, value) in cached {
+            map.entry(key).or_insert(value);
+        }
+        return Ok(());
+    }
+
+    Vault::new().load_into(map)
+}
+
+/// Loads the *.env.vault* file like [`dotenv`], returning `None` if neither a *.env.vault* nor a
+/// regular *.env* file is found and nothing was loaded.
+///
+/// See [`Vault::try_load`].
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     if dotenv_vault::dotenv_try().transpose()?.is_none() {
+///         println!("no .env.vault or .env file, using the environment as is");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_try() -> Option<Result<()>> {
+    if global::apply(false) {
+        return Some(Ok(()));
+    }
+
+    Vault::new().try_load()
+}
+
+/// Loads the *.env.vault* file from [`env::current_dir`](std::env::current_dir) like [`dotenv`],
+/// using the given dotenv key instead of the *DOTENV_KEY* environment variable.
+///
+/// If variables with the same names already exist in the environment, then their values will be
+/// preserved.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let dotenv_key = "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production";
+///     dotenv_vault::from_env(dotenv_key)?;
+///     Ok(())
+/// }
+/// ```
+pub fn from_env(dotenv_key: &str) -> Result<()> {
+    VaultBuilder::new().with_key(dotenv_key).build().load()
+}
+
+/// Loads the *.env.vault* file like [`from_env`], overriding any existing environment variables of
+/// the same name.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let dotenv_key = "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production";
+///     dotenv_vault::from_env_override(dotenv_key)?;
+///     Ok(())
+/// }
+/// ```
+pub fn from_env_override(dotenv_key: &str) -> Result<()> {
+    VaultBuilder::new()
+        .with_key(dotenv_key)
+        .build()
+        .load_override()
+}
+
+/// Reads all variables from the *.env.vault* file like [`dotenv_to_map`], reporting where they
+/// came from and how long decryption took instead of returning them.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let stats = dotenv_vault::dotenv_stats()?;
+///     println!("{} variables in {:?}", stats.variable_count, stats.decryption_duration);
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_stats() -> Result<VaultStats> {
+    Vault::new().stats()
+}
+
+/// Checks that the *.env.vault* file in [`env::current_dir`](std::env::current_dir) can be
+/// loaded with the *DOTENV_KEY* environment variable, without modifying the environment.
+///
+/// See [`Vault::preflight_check`] for the checks that are run.
+///
+/// # Examples
+/// ```no_run
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let report = dotenv_vault::dotenv_preflight()?;
+///     if !report.warnings.is_empty() {
+///         eprintln!("{}", report.warnings.join("\n"));
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_preflight() -> Result<PreflightReport> {
+    Vault::new().preflight_check()
+}
+
+/// Compares the variables decrypted from the *.env.vault* file at `path` with two different
+/// dotenv keys, typically for two environments, without modifying the environment.
+///
+/// # Examples
+/// ```no_run
+/// use std::path::Path;
+///
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let diff = dotenv_vault::dotenv_diff(
+///         "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production",
+///         "dotenv://:key_5678@dotenv.org/vault/.env.vault?environment=staging",
+///         Path::new(".env.vault"),
+///     )?;
+///     for entry in diff {
+///         println!("{}: {:?} -> {:?}", entry.key, entry.left, entry.right);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn dotenv_diff(key_a: &str, key_b: &str, path: &Path) -> Result<Vec<VaultDiff>> {
+    Vault::from_path(path, key_a).diff(&Vault::from_path(path, key_b))
+}
+
+/// Deserializes all variables from the *.env.vault* file, or a regular *.env* file if the key or
+/// vault cannot be found, into `T` without modifying the environment.
+///
+/// Variable names are matched case-insensitively against field names.
+///
+/// # Examples
+/// ```no_run
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     s3_bucket: String,
+///     port: u16,
+/// }
+///
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let config: Config = dotenv_vault::dotenv_deserialize()?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "serde")]
+pub fn dotenv_deserialize<T>() -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    Vault::new().deserialize()
+}
+
 #[cfg(test)]
 mod tests {
     use serial_test::serial;
+    use static_assertions::assert_impl_all;
     use std::{env, fs::File, io::prelude::*};
     use tempfile::tempdir;
 
+    // Vaults and errors are shared between threads and held across await points
+    assert_impl_all!(super::Vault: Send, Sync);
+    assert_impl_all!(super::VaultBuilder: Send, Sync);
+    assert_impl_all!(super::VaultConfig: Send, Sync);
+    assert_impl_all!(super::Error: Send, Sync);
+
     #[test]
     #[serial] // Run serially due to env modifications
     fn dotenv_ok() {
@@ -176,4 +692,321 @@ mod tests {
         env::remove_var("TESTKEY");
         env::set_current_dir(cwd).unwrap();
     }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn dotenv_to_map_ok() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        let result = super::dotenv_to_map();
+        assert!(result.is_ok());
+
+        let map = result.unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("ALPHA").unwrap(), "zeta");
+        assert!(env::var("ALPHA").is_err());
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::set_current_dir(cwd).unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn dotenv_try_nothing_found() {
+        env::remove_var("DOTENV_KEY");
+
+        let tmp = tempdir().unwrap();
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        let result = super::dotenv_try();
+        assert!(result.is_none());
+
+        env::set_current_dir(cwd).unwrap();
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn from_env_inline_key() {
+        env::remove_var("DOTENV_KEY");
+        env::set_var("ALPHA", "existing");
+
+        let tmp = tempdir().unwrap();
+        let mut vault = File::create(tmp.path().join(".env.vault")).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        let dotenv_key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+        let result = super::from_env(dotenv_key);
+        let alpha = env::var("ALPHA");
+        let override_result = super::from_env_override(dotenv_key);
+        let alpha_override = env::var("ALPHA");
+
+        env::set_current_dir(cwd).unwrap();
+        env::remove_var("ALPHA");
+
+        assert!(result.is_ok());
+        assert_eq!(alpha.unwrap(), "existing");
+        assert!(override_result.is_ok());
+        assert_eq!(alpha_override.unwrap(), "zeta");
+        assert!(env::var("DOTENV_KEY").is_err());
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn dotenv_stats_ok() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(&vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        let result = super::dotenv_stats();
+        assert!(result.is_ok());
+
+        let stats = result.unwrap();
+        assert_eq!(
+            stats.path.map(|path| path.file_name().unwrap().to_owned()),
+            Some(".env.vault".into())
+        );
+        assert_eq!(stats.environment.as_deref(), Some("production"));
+        assert_eq!(stats.variable_count, 1);
+        assert!(!stats.fallback_used);
+        assert!(env::var("ALPHA").is_err());
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::set_current_dir(cwd).unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn dotenv_get_typed_ok() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"9gAS9K5KNxQG6o8Jx2m9OMPro7ieKf8S2W5Ww0LW0iYQfyr06FzEpeeobdOHR8wYY+OpP0LIdQRObLWyFOU3a9rmiSVVBD14elG8bH2MsAYTii7OHuQQqorTUiMK6ZCH/dk=\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        assert_eq!(super::dotenv_get_typed::<u16>("PORT").unwrap(), 8080);
+        assert!(super::dotenv_get_typed::<bool>("DEBUG").unwrap());
+        assert!(super::dotenv_get_typed::<u16>("MISSING").is_err());
+        assert!(env::var("PORT").is_err());
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::set_current_dir(cwd).unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn dotenv_require_ok() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"9gAS9K5KNxQG6o8Jx2m9OMPro7ieKf8S2W5Ww0LW0iYQfyr06FzEpeeobdOHR8wYY+OpP0LIdQRObLWyFOU3a9rmiSVVBD14elG8bH2MsAYTii7OHuQQqorTUiMK6ZCH/dk=\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        assert_eq!(
+            super::dotenv_require("URL").unwrap(),
+            "https://example.com/app"
+        );
+        assert_eq!(super::dotenv_require_typed::<u16>("PORT").unwrap(), 8080);
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::remove_var("PORT");
+        env::remove_var("DEBUG");
+        env::remove_var("URL");
+        env::set_current_dir(cwd).unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn dotenv_require_missing() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        let result = super::dotenv_require("BETA");
+        assert!(
+            matches!(result, Err(super::Error::RequiredVariableMissing(ref key)) if key == "BETA")
+        );
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::remove_var("ALPHA");
+        env::set_current_dir(cwd).unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn dotenv_len_ok() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        assert_eq!(super::dotenv_len().unwrap(), 1);
+        assert!(!super::dotenv_is_empty().unwrap());
+        assert!(env::var("ALPHA").is_err());
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::set_current_dir(cwd).unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn dotenv_into_ok() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        let mut map = std::collections::HashMap::new();
+        let result = super::dotenv_into(&mut map);
+        assert!(result.is_ok());
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("ALPHA").unwrap(), "zeta");
+        assert!(env::var("ALPHA").is_err());
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::set_current_dir(cwd).unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    #[cfg(feature = "serde")]
+    fn dotenv_deserialize_ok() {
+        #[derive(serde::Deserialize)]
+        struct Config {
+            alpha: String,
+        }
+
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        let result = super::dotenv_deserialize::<Config>();
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().alpha, "zeta");
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::set_current_dir(cwd).unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn init_caches_vault() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let tmp = tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(&vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        assert!(super::init().is_ok());
+
+        // The vault is not read again once cached
+        std::fs::remove_file(&vault_path).unwrap();
+        assert!(super::init().is_ok());
+        assert_eq!(super::dotenv_get("ALPHA").unwrap().unwrap(), "zeta");
+        assert_eq!(super::dotenv_to_map().unwrap().len(), 1);
+
+        assert!(super::dotenv().is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "zeta");
+
+        super::reset_global();
+        assert!(super::dotenv_to_map().is_err());
+
+        tmp.close().unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::remove_var("ALPHA");
+        env::set_current_dir(cwd).unwrap();
+    }
 }
@@ -0,0 +1,50 @@
+use super::errors::Result;
+
+use secret_service::{blocking::SecretService, EncryptionType};
+use std::{collections::HashMap, env};
+
+/// Attribute of the secret service items holding dotenv keys, set to their project directory
+const ATTRIBUTE: &str = "dotenv_vault_cwd";
+
+/// Value of [`ATTRIBUTE`] for the current directory
+fn cwd() -> Result<String> {
+    Ok(env::current_dir()?.display().to_string())
+}
+
+/// Read the dotenv key of the current directory from the default collection of the secret
+/// service
+///
+/// Returns `None` if there is no item for the current directory.
+pub(crate) fn read_key() -> Result<Option<String>> {
+    let cwd = cwd()?;
+    let service = SecretService::connect(EncryptionType::Dh)?;
+    let collection = service.get_default_collection()?;
+    collection.ensure_unlocked()?;
+
+    let items = collection.search_items(HashMap::from([(ATTRIBUTE, cwd.as_str())]))?;
+    let item = match items.first() {
+        Some(item) => item,
+        None => return Ok(None),
+    };
+
+    let key = item.get_secret()?;
+    Ok(Some(String::from_utf8_lossy(&key).trim().to_string()).filter(|key| !key.is_empty()))
+}
+
+/// Store the dotenv key of the current directory in the default collection of the secret
+/// service, replacing any existing one
+pub(crate) fn save_key(key: &str) -> Result<()> {
+    let cwd = cwd()?;
+    let service = SecretService::connect(EncryptionType::Dh)?;
+    let collection = service.get_default_collection()?;
+    collection.ensure_unlocked()?;
+
+    collection.create_item(
+        &format!("DOTENV_KEY for {}", cwd),
+        HashMap::from([(ATTRIBUTE, cwd.as_str())]),
+        key.trim().as_bytes(),
+        true,
+        "text/plain",
+    )?;
+    Ok(())
+}
@@ -1,12 +1,43 @@
-use std::fmt::Display;
+use std::{env, fmt::Display};
+
+/// How much dotenv-vault logs to stderr
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Log nothing
+    None,
+    /// Log warnings only
+    #[default]
+    Warn,
+    /// Log warnings and informational messages
+    Info,
+}
+
+impl LogLevel {
+    /// Read the log level from *DOTENV_VAULT_LOG_LEVEL*, which is one of `none`, `warn` or
+    /// `info`, defaulting to [`LogLevel::Warn`]
+    pub(crate) fn from_env() -> Self {
+        match env::var("DOTENV_VAULT_LOG_LEVEL")
+            .map(|level| level.to_lowercase())
+            .as_deref()
+        {
+            Ok("none") => LogLevel::None,
+            Ok("info") => LogLevel::Info,
+            _ => LogLevel::Warn,
+        }
+    }
+}
 
 /// Macro for generating logging functions
 macro_rules! log_fn {
-    ($name:tt, $level:tt) => {
-        pub fn $name<T>(message: T)
+    ($name:tt, $level:tt, $min:expr) => {
+        pub fn $name<T>(filter: LogLevel, message: T)
         where
             T: Display,
         {
+            if filter < $min {
+                return;
+            }
+
             eprintln!(
                 "[dotenv-vault@{}][{}] {}",
                 env!("CARGO_PKG_VERSION"),
@@ -17,5 +48,33 @@ macro_rules! log_fn {
     };
 }
 
-log_fn!(info, "INFO");
-log_fn!(warn, "WARN");
+log_fn!(info, "INFO", LogLevel::Info);
+log_fn!(warn, "WARN", LogLevel::Warn);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn log_level_from_env() {
+        env::set_var("DOTENV_VAULT_LOG_LEVEL", "none");
+        assert_eq!(LogLevel::from_env(), LogLevel::None);
+
+        env::set_var("DOTENV_VAULT_LOG_LEVEL", "INFO");
+        assert_eq!(LogLevel::from_env(), LogLevel::Info);
+
+        env::set_var("DOTENV_VAULT_LOG_LEVEL", "verbose");
+        assert_eq!(LogLevel::from_env(), LogLevel::Warn);
+
+        env::remove_var("DOTENV_VAULT_LOG_LEVEL");
+        assert_eq!(LogLevel::from_env(), LogLevel::Warn);
+    }
+
+    #[test]
+    fn log_level_order() {
+        assert!(LogLevel::None < LogLevel::Warn);
+        assert!(LogLevel::Warn < LogLevel::Info);
+    }
+}
@@ -1,7 +1,12 @@
 use argh::FromArgs;
+use dotenv_vault::{LogLevel, Vault, VaultBuilder, VaultConfig, VaultDiff};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
-use std::path::PathBuf;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::process::{exit, Command, Stdio};
+use std::str::FromStr;
 
 #[derive(FromArgs, PartialEq, Debug)]
 /// The CLI program to load the .env.vault file and run the specified program with the specified arguments.
@@ -11,19 +16,86 @@ use std::process::{exit, Command, Stdio};
 /// Example:
 /// dotenv-vault run -- my_program arg1 arg2
 struct Opts {
+    #[argh(option, from_str_fn(parse_log_level))]
+    /// how much to log to stderr: none, warn or info (default: DOTENV_VAULT_LOG_LEVEL or warn)
+    log_level: Option<LogLevel>,
+
+    #[argh(switch, short = 'q')]
+    /// log nothing, same as --log-level none
+    quiet: bool,
+
+    #[argh(switch, short = 'v')]
+    /// log informational messages too, same as --log-level info
+    verbose: bool,
+
     #[argh(subcommand)]
     commands: Commands,
 }
 
+impl Opts {
+    /// The log level chosen on the command line, if any
+    fn log_level(&self) -> Result<Option<LogLevel>, String> {
+        let levels = [
+            self.log_level,
+            self.quiet.then_some(LogLevel::None),
+            self.verbose.then_some(LogLevel::Info),
+        ];
+
+        match levels.iter().flatten().collect::<Vec<_>>()[..] {
+            [] => Ok(None),
+            [level] => Ok(Some(*level)),
+            _ => Err("--log-level, --quiet and --verbose cannot be combined".to_string()),
+        }
+    }
+}
+
+fn parse_log_level(value: &str) -> Result<LogLevel, String> {
+    match value {
+        "none" => Ok(LogLevel::None),
+        "warn" => Ok(LogLevel::Warn),
+        "info" => Ok(LogLevel::Info),
+        _ => Err(format!(
+            "unknown log level '{}', expected none, warn or info",
+            value
+        )),
+    }
+}
+
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand)]
 enum Commands {
     Run(Run),
+    Validate(Validate),
+    Rotate(Rotate),
+    Encrypt(Encrypt),
+    Diff(Diff),
+    Init(Init),
+    GenerateKey(GenerateKey),
+    Version(Version),
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
 /// Load the .env.vault file and run the specified program with the specified arguments.
-#[argh(subcommand, name = "run")]
+#[argh(
+    subcommand,
+    name = "run",
+    note = "Once the program runs, its exit code is passed through.",
+    error_code(
+        1,
+        "The DOTENV_KEY, .env.vault file, environment or a variable was not found."
+    ),
+    error_code(
+        2,
+        "The .env.vault file could not be decrypted, e.g. with the wrong key."
+    ),
+    error_code(3, "The DOTENV_KEY, .env.vault file or its variables are invalid."),
+    error_code(4, "Reading the .env.vault file failed, locally or remotely."),
+    error_code(10, "The program could not be executed."),
+    error_code(11, "The working directory could not be changed."),
+    error_code(12, "No program was given."),
+    error_code(13, "The output file could not be written."),
+    error_code(14, "The options are invalid.")
+)]
 struct Run {
     #[argh(switch, long = "override")]
     /// whether to override the existing environment variables
@@ -33,26 +105,368 @@ struct Run {
     /// current working directory to run the program in
     cwd: Option<PathBuf>,
 
+    #[argh(switch)]
+    /// print the loaded environment variables instead of running a program
+    print_env: bool,
+
+    #[argh(option)]
+    /// write the loaded environment variables to this file in dotenv format before running the
+    /// program, or instead of printing them with --print-env
+    output: Option<PathBuf>,
+
+    #[argh(option, default = "Format::Shell")]
+    /// format of the variables printed by --print-env: shell, json, toml or dotenv (default: shell)
+    format: Format,
+
+    #[argh(positional, greedy)]
+    /// the program to run, followed by the arguments to pass to it
+    command: Vec<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Check that the .env.vault file can be decrypted with the DOTENV_KEY.
+#[argh(subcommand, name = "validate")]
+struct Validate {
+    #[argh(option)]
+    /// current working directory to look for the .env.vault file in
+    cwd: Option<PathBuf>,
+
+    #[argh(option)]
+    /// path of the .env.vault file (default: .env.vault)
+    env_file: Option<PathBuf>,
+
+    #[argh(option)]
+    /// dotenv key to use instead of the DOTENV_KEY environment variable
+    dotenv_key: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Re-encrypt the .env.vault file entry of one DOTENV_KEY with another key.
+#[argh(subcommand, name = "rotate")]
+struct Rotate {
+    #[argh(option)]
+    /// the DOTENV_KEY the .env.vault file is currently encrypted with
+    old_key: String,
+
+    #[argh(option)]
+    /// the DOTENV_KEY to encrypt the .env.vault file with
+    new_key: String,
+
+    #[argh(switch)]
+    /// overwrite the .env.vault file instead of printing the rotated file
+    in_place: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Encrypt plaintext .env contents into a .env.vault file entry.
+#[argh(subcommand, name = "encrypt")]
+struct Encrypt {
+    #[argh(option)]
+    /// the DOTENV_KEY to encrypt with, which also names the environment of the entry
+    key: String,
+
+    #[argh(option)]
+    /// the plaintext .env contents to encrypt
+    value: String,
+
+    #[argh(option)]
+    /// append the entry to this .env.vault file instead of printing it
+    append_to: Option<PathBuf>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Show the variables that differ between the environments of two DOTENV_KEYs.
+#[argh(subcommand, name = "diff")]
+struct Diff {
     #[argh(positional)]
-    /// the program to run
-    program: String,
+    /// the DOTENV_KEY of the environment to compare from
+    key_a: String,
 
     #[argh(positional)]
-    /// the arguments to pass to the program
-    program_args: Vec<String>,
+    /// the DOTENV_KEY of the environment to compare to
+    key_b: String,
+
+    #[argh(option)]
+    /// path of the .env.vault file (default: .env.vault)
+    file: Option<PathBuf>,
+
+    #[argh(switch)]
+    /// only print the names of the variables, not their values
+    keys_only: bool,
 }
 
+#[derive(FromArgs, PartialEq, Debug)]
+/// Create a .env.vault file with a new DOTENV_KEY, encrypting the .env file if there is one.
+#[argh(subcommand, name = "init")]
+struct Init {
+    #[argh(option)]
+    /// current working directory to create the .env.vault file in
+    cwd: Option<PathBuf>,
+
+    #[argh(switch)]
+    /// overwrite an existing .env.vault file
+    force: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Generate a new random DOTENV_KEY for an environment.
+#[argh(subcommand, name = "generate-key")]
+struct GenerateKey {
+    #[argh(positional)]
+    /// the environment of the key, taking precedence over --environment
+    name: Option<String>,
+
+    #[argh(option, default = "String::from(\"production\")")]
+    /// the environment of the key (default: production)
+    environment: String,
+
+    #[argh(option, default = "KeyFormat::Text")]
+    /// format of the printed key: text or json (default: text)
+    format: KeyFormat,
+
+    #[argh(option)]
+    /// also append the key to this .env.keys file as DOTENV_KEY_<ENVIRONMENT>
+    save_to: Option<PathBuf>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Print the version, git commit, build date and Rust version of dotenv-vault.
+#[argh(subcommand, name = "version")]
+struct Version {}
+
+#[derive(PartialEq, Debug)]
+enum Format {
+    Shell,
+    Json,
+    Toml,
+    Dotenv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shell" => Ok(Format::Shell),
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            "dotenv" => Ok(Format::Dotenv),
+            _ => Err(format!(
+                "unknown format '{}', expected shell, json, toml or dotenv",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+enum KeyFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for KeyFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(KeyFormat::Text),
+            "json" => Ok(KeyFormat::Json),
+            _ => Err(format!("unknown format '{}', expected text or json", s)),
+        }
+    }
+}
+
+impl Format {
+    /// Format the variables, sorted by name
+    fn render(&self, vars: HashMap<String, String>) -> String {
+        let vars: BTreeMap<String, String> = vars.into_iter().collect();
+
+        match self {
+            Format::Shell => vars
+                .iter()
+                .map(|(key, value)| format!("{}={}\n", key, shell_escape(value)))
+                .collect(),
+            Format::Json => {
+                let mut json =
+                    serde_json::to_string_pretty(&vars).expect("string map is valid json");
+                json.push('\n');
+                json
+            }
+            Format::Toml => {
+                let table = BTreeMap::from([("env", vars)]);
+                toml::to_string(&table).expect("string map is valid toml")
+            }
+            Format::Dotenv => vars
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"\n", key, dotenv_escape(value)))
+                .collect(),
+        }
+    }
+}
+
+/// Quote a value for a POSIX shell, leaving it bare when that is safe
+fn shell_escape(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.,:/@%+=".contains(c));
+
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Escape a value for a double quoted dotenv value
+fn dotenv_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '"' | '$' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render a variable of a diff, prefixed with `-` if it was removed, `+` if it was added and `~`
+/// if its value changed
+fn render_diff(diff: &VaultDiff, keys_only: bool) -> String {
+    let prefix = match (&diff.left, &diff.right) {
+        (Some(_), None) => '-',
+        (None, Some(_)) => '+',
+        _ => '~',
+    };
+
+    if keys_only {
+        return format!("{}{}", prefix, diff.key);
+    }
+
+    let value = |value: &str| format!("\"{}\"", dotenv_escape(value));
+    match (&diff.left, &diff.right) {
+        (Some(left), Some(right)) => {
+            format!("{}{}={} -> {}", prefix, diff.key, value(left), value(right))
+        }
+        (Some(only), None) | (None, Some(only)) => {
+            format!("{}{}={}", prefix, diff.key, value(only))
+        }
+        (None, None) => format!("{}{}", prefix, diff.key),
+    }
+}
+
+/// Exit codes of failures that only happen in the CLI, kept apart from the `1` to `4` of
+/// [`dotenv_vault::Error::into_exit_code`]
 #[derive(Debug)]
 #[repr(i32)]
 enum CLIError {
-    EnvLoad = 1,
-    EnvOverrideLoad = 2,
-    ProgramExecution = 3,
-    CwdChange = 4,
+    ProgramExecution = 10,
+    CwdChange = 11,
+    MissingProgram = 12,
+    OutputWrite = 13,
+    InvalidOptions = 14,
+}
+
+/// Replace the `old_entry` line of a .env.vault file with `new_line`, dropping any existing
+/// `new_entry` line so the rotated entry is not declared twice
+fn replace_entry(vault: &str, old_entry: &str, new_entry: &str, new_line: &str) -> String {
+    let declares = |line: &str, entry: &str| {
+        line.trim_start()
+            .strip_prefix(entry)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    };
+
+    let mut rotated = String::with_capacity(vault.len());
+    for line in vault.lines() {
+        if declares(line, old_entry) {
+            rotated.push_str(new_line);
+            rotated.push('\n');
+        } else if !declares(line, new_entry) {
+            rotated.push_str(line);
+            rotated.push('\n');
+        }
+    }
+    rotated
+}
+
+/// Append a line to a file, starting a new line first if the file does not end with one
+fn append_line(path: &Path, line: &str) -> io::Result<()> {
+    let file = OpenOptions::new().append(true).create(true).open(path)?;
+    append_line_to(path, file, line)
+}
+
+/// Append a line like [`append_line`] to a file only readable by the current user
+fn append_private_line(path: &Path, line: &str) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        options.mode(0o600);
+        let file = options.open(path)?;
+        // The mode only applies to newly created files
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        append_line_to(path, file, line)
+    }
+
+    #[cfg(not(unix))]
+    {
+        append_line_to(path, options.open(path)?, line)
+    }
+}
+
+/// Append a line to the file opened for appending from `path`
+fn append_line_to(path: &Path, mut file: std::fs::File, line: &str) -> io::Result<()> {
+    let needs_newline = match std::fs::read(path) {
+        Ok(contents) => !contents.is_empty() && !contents.ends_with(b"\n"),
+        Err(err) => return Err(err),
+    };
+
+    if needs_newline {
+        file.write_all(b"\n")?;
+    }
+    writeln!(file, "{}", line)
+}
+
+/// Write the contents to a file only readable by the current user
+fn write_private_file(path: &Path, contents: &str) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        options.mode(0o600);
+        let mut file = options.open(path)?;
+        // The mode only applies to newly created files
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents.as_bytes())
+    }
+
+    #[cfg(not(unix))]
+    {
+        options.open(path)?.write_all(contents.as_bytes())
+    }
 }
 
 fn main() {
     let opts = argh::from_env::<Opts>();
+    let log_level = opts.log_level().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(CLIError::InvalidOptions as i32);
+    });
+    let mut config = VaultConfig::default();
+    if let Some(log_level) = log_level {
+        config.log_level = log_level;
+    }
 
     match opts.commands {
         Commands::Run(run_opts) => {
@@ -65,29 +479,61 @@ fn main() {
                 });
             }
 
+            if run_opts.print_env || run_opts.output.is_some() {
+                let vars = Vault::new_with_config(config.clone())
+                    .to_map()
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to load env: {}", err);
+                        exit(err.into_exit_code());
+                    });
+
+                if let Some(output) = &run_opts.output {
+                    let output = current_cwd.join(output);
+                    write_private_file(&output, &Format::Dotenv.render(vars)).unwrap_or_else(
+                        |err| {
+                            eprintln!("Failed to write {}: {}", output.display(), err);
+                            exit(CLIError::OutputWrite as i32);
+                        },
+                    );
+                } else {
+                    print!("{}", run_opts.format.render(vars));
+                }
+
+                if run_opts.print_env {
+                    return;
+                }
+            }
+
+            let (program, program_args) = run_opts.command.split_first().unwrap_or_else(|| {
+                eprintln!("Missing the program to run");
+                exit(CLIError::MissingProgram as i32);
+            });
+
             // Load the .env.vault file
             if run_opts.override_ {
-                dotenv_vault::dotenv_override().unwrap_or_else(|err| {
-                    eprintln!("Failed to load env: {}", err);
-                    exit(CLIError::EnvOverrideLoad as i32);
-                });
+                Vault::new_with_config(config)
+                    .load_override()
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to load env: {}", err);
+                        exit(err.into_exit_code());
+                    });
             } else {
-                dotenv_vault::dotenv().unwrap_or_else(|err| {
+                Vault::new_with_config(config).load().unwrap_or_else(|err| {
                     eprintln!("Failed to load env: {}", err);
-                    exit(CLIError::EnvLoad as i32);
+                    exit(err.into_exit_code());
                 });
             };
 
             // Run the specified program with the specified arguments
-            let output = Command::new(&run_opts.program)
-                .args(run_opts.program_args)
+            let output = Command::new(program)
+                .args(program_args)
                 .envs(env::vars())
                 .stdin(Stdio::inherit())
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit())
                 .output()
                 .unwrap_or_else(|err| {
-                    eprintln!("Failed to execute program {}: {}", run_opts.program, err);
+                    eprintln!("Failed to execute program {}: {}", program, err);
                     exit(CLIError::ProgramExecution as i32);
                 });
 
@@ -106,5 +552,202 @@ fn main() {
                 );
             }
         }
+        Commands::Validate(validate_opts) => {
+            if let Some(given_cwd) = validate_opts.cwd {
+                env::set_current_dir(given_cwd).unwrap_or_else(|err| {
+                    eprintln!("Failed to change the current working directory: {}", err);
+                    exit(CLIError::CwdChange as i32);
+                });
+            }
+
+            let dotenv_key = validate_opts
+                .dotenv_key
+                .or_else(|| env::var(&config.key_var_name).ok());
+            let env_file = validate_opts
+                .env_file
+                .unwrap_or_else(|| PathBuf::from(".env.vault"));
+            let builder = VaultBuilder::new().with_config(config).with_path(env_file);
+            let vault = match dotenv_key {
+                Some(key) => builder.with_key(&key).build(),
+                None => builder.build(),
+            };
+
+            match vault.verify() {
+                Ok((environment, count)) => {
+                    println!(
+                        "Decrypted {} variables from the {} environment",
+                        count, environment
+                    );
+                }
+                Err(err) => {
+                    eprintln!("Failed to validate the vault: {}", err);
+                    exit(err.into_exit_code());
+                }
+            }
+        }
+        Commands::Rotate(rotate_opts) => {
+            let path = PathBuf::from(".env.vault");
+            let vault = dotenv_vault::Vault::from_path(&path, &rotate_opts.old_key);
+
+            let (old_entry, plaintext) = vault.decrypt_entry().unwrap_or_else(|err| {
+                eprintln!("Failed to decrypt the vault with the old key: {}", err);
+                exit(err.into_exit_code());
+            });
+            let (_, new_entry) = vault
+                .instructions(&rotate_opts.new_key)
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to parse the new key: {}", err);
+                    exit(err.into_exit_code());
+                });
+            let ciphertext = Vault::encrypt_for_key(&plaintext, &rotate_opts.new_key)
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to encrypt the vault with the new key: {}", err);
+                    exit(err.into_exit_code());
+                });
+
+            let contents = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+                eprintln!("Failed to read {}: {}", path.display(), err);
+                exit(dotenv_vault::Error::from(err).into_exit_code());
+            });
+            let new_line = format!("{}=\"{}\"", new_entry, ciphertext);
+            let rotated = replace_entry(&contents, &old_entry, &new_entry, &new_line);
+
+            if rotate_opts.in_place {
+                std::fs::write(&path, rotated).unwrap_or_else(|err| {
+                    eprintln!("Failed to write {}: {}", path.display(), err);
+                    exit(CLIError::OutputWrite as i32);
+                });
+            } else {
+                print!("{}", rotated);
+            }
+        }
+        Commands::Encrypt(encrypt_opts) => {
+            let vault = dotenv_vault::Vault::from_key(&encrypt_opts.key);
+            let (_, entry) = vault.instructions(&encrypt_opts.key).unwrap_or_else(|err| {
+                eprintln!("Failed to parse the key: {}", err);
+                exit(err.into_exit_code());
+            });
+            let ciphertext =
+                Vault::encrypt_for_key(encrypt_opts.value.as_bytes(), &encrypt_opts.key)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to encrypt the value: {}", err);
+                        exit(err.into_exit_code());
+                    });
+            let line = format!("{}=\"{}\"", entry, ciphertext);
+
+            if let Some(append_to) = encrypt_opts.append_to {
+                append_line(&append_to, &line).unwrap_or_else(|err| {
+                    eprintln!("Failed to write {}: {}", append_to.display(), err);
+                    exit(CLIError::OutputWrite as i32);
+                });
+            } else {
+                println!("{}", line);
+            }
+        }
+        Commands::Diff(diff_opts) => {
+            let path = diff_opts
+                .file
+                .unwrap_or_else(|| PathBuf::from(".env.vault"));
+            let vault = |key: &str| {
+                VaultBuilder::new()
+                    .with_key(key)
+                    .with_path(&path)
+                    .with_config(config.clone())
+                    .build()
+            };
+
+            let diff = vault(&diff_opts.key_a)
+                .diff(&vault(&diff_opts.key_b))
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to diff the vault: {}", err);
+                    exit(err.into_exit_code());
+                });
+
+            for variable in &diff {
+                println!("{}", render_diff(variable, diff_opts.keys_only));
+            }
+        }
+        Commands::Init(init_opts) => {
+            if let Some(given_cwd) = init_opts.cwd {
+                env::set_current_dir(given_cwd).unwrap_or_else(|err| {
+                    eprintln!("Failed to change the current working directory: {}", err);
+                    exit(CLIError::CwdChange as i32);
+                });
+            }
+
+            let path = PathBuf::from(".env.vault");
+            if path.exists() && !init_opts.force {
+                eprintln!(
+                    "{} already exists, pass --force to overwrite it",
+                    path.display()
+                );
+                exit(CLIError::OutputWrite as i32);
+            }
+
+            let plaintext = match std::fs::read(".env") {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+                Err(err) => {
+                    eprintln!("Failed to read .env: {}", err);
+                    exit(dotenv_vault::Error::from(err).into_exit_code());
+                }
+            };
+
+            let (dotenv_key, _) = Vault::generate_key("development");
+            let vault = Vault::from_key(&dotenv_key);
+            let (key, entry) = vault.instructions(&dotenv_key).unwrap_or_else(|err| {
+                eprintln!("Failed to parse the generated key: {}", err);
+                exit(err.into_exit_code());
+            });
+            let ciphertext = Vault::encrypt(&plaintext, &key, false).unwrap_or_else(|err| {
+                eprintln!("Failed to encrypt .env: {}", err);
+                exit(err.into_exit_code());
+            });
+
+            std::fs::write(&path, format!("{}=\"{}\"\n", entry, ciphertext)).unwrap_or_else(
+                |err| {
+                    eprintln!("Failed to write {}: {}", path.display(), err);
+                    exit(CLIError::OutputWrite as i32);
+                },
+            );
+
+            eprintln!(
+                "Created {}. Store this DOTENV_KEY securely, it cannot be recovered:",
+                path.display()
+            );
+            println!("{}", dotenv_key);
+        }
+        Commands::GenerateKey(generate_key_opts) => {
+            let environment = generate_key_opts
+                .name
+                .unwrap_or(generate_key_opts.environment);
+            let (dotenv_key, _) = Vault::generate_key(&environment);
+
+            if let Some(save_to) = generate_key_opts.save_to {
+                let line = format!(
+                    "DOTENV_KEY_{}=\"{}\"",
+                    environment.to_uppercase(),
+                    dotenv_key
+                );
+                append_private_line(&save_to, &line).unwrap_or_else(|err| {
+                    eprintln!("Failed to write {}: {}", save_to.display(), err);
+                    exit(CLIError::OutputWrite as i32);
+                });
+            }
+
+            match generate_key_opts.format {
+                KeyFormat::Text => println!("{}", dotenv_key),
+                KeyFormat::Json => println!(
+                    "{}",
+                    serde_json::json!({ "dotenv_key": dotenv_key, "environment": environment })
+                ),
+            }
+        }
+        Commands::Version(_) => {
+            println!("dotenv-vault {}", env!("CARGO_PKG_VERSION"));
+            println!("commit: {}", env!("DOTENV_VAULT_GIT_HASH"));
+            println!("build date: {}", env!("DOTENV_VAULT_BUILD_DATE"));
+            println!("rustc: {}", env!("DOTENV_VAULT_RUSTC_VERSION"));
+        }
     }
 }
@@ -0,0 +1,14 @@
+use super::errors::Result;
+
+use reqwest::blocking::{Client, Response};
+
+/// Fetch a *.env.vault* file over HTTP(S), sending `token` as a Bearer token when given
+pub(crate) fn fetch(url: &str, token: Option<&str>) -> Result<Response> {
+    let mut request = Client::new().get(url);
+
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    Ok(request.send()?.error_for_status()?)
+}
@@ -0,0 +1,36 @@
+use super::errors::{Error, Result};
+
+use aws_sdk_s3::Client;
+
+/// Fetch a *.env.vault* file from S3, using the region and credentials from the standard AWS
+/// environment variables and configuration files
+pub(crate) async fn fetch_async(bucket: &str, key: &str) -> Result<Vec<u8>> {
+    let config = aws_config::load_from_env().await;
+    let client = Client::new(&config);
+
+    let object = client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|err| Error::S3Error(Box::new(err)))?;
+    let body = object
+        .body
+        .collect()
+        .await
+        .map_err(|err| Error::S3Error(Box::new(err)))?;
+
+    Ok(body.into_bytes().to_vec())
+}
+
+/// Fetch a *.env.vault* file from S3 like [`fetch_async`], blocking on a new single-threaded
+/// runtime
+///
+/// This must not be called from within an async runtime, use [`fetch_async`] there instead.
+pub(crate) fn fetch(bucket: &str, key: &str) -> Result<Vec<u8>> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(fetch_async(bucket, key))
+}
@@ -0,0 +1,44 @@
+#[cfg(feature = "metrics")]
+use super::errors::Result;
+
+#[cfg(feature = "opentelemetry")]
+use opentelemetry::{
+    global,
+    trace::{Span, Tracer},
+    KeyValue,
+};
+#[cfg(feature = "opentelemetry")]
+use std::time::SystemTime;
+
+/// Report a finished [`Vault::load`](crate::Vault::load) as an OpenTelemetry span
+///
+/// The span is created after the fact with `started` as its start time, so the duration covers
+/// the whole load.
+#[cfg(feature = "opentelemetry")]
+pub(crate) fn report_load(started: SystemTime, path: String, environment: String, success: bool) {
+    let duration_ms = started.elapsed().map_or(0, |elapsed| elapsed.as_millis());
+
+    let tracer = global::tracer("dotenv_vault");
+    let mut span = tracer
+        .span_builder("dotenv_vault.load")
+        .with_start_time(started)
+        .with_attributes([
+            KeyValue::new("vault.path", path),
+            KeyValue::new("vault.environment", environment),
+            KeyValue::new("vault.success", success),
+            KeyValue::new("vault.duration_ms", duration_ms as i64),
+        ])
+        .start(&tracer);
+    span.end();
+}
+
+/// Count a finished [`Vault::load`](crate::Vault::load) as a success or as an error labelled with
+/// its [`error_code`](crate::Error::error_code)
+#[cfg(feature = "metrics")]
+pub(crate) fn count_load(result: &Result<()>) {
+    match result {
+        Ok(()) => metrics::counter!("dotenv_vault.load.success").increment(1),
+        Err(err) => metrics::counter!("dotenv_vault.load.error", "error_code" => err.error_code())
+            .increment(1),
+    }
+}
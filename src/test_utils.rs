@@ -0,0 +1,116 @@
+//! Helpers for testing crates that load their configuration with dotenv-vault
+//!
+//! [`setup_vault_env`] writes a *.env.vault* file to a temporary directory, makes it the current
+//! directory and sets *DOTENV_KEY*, so that [`Vault::new`](crate::Vault::new) and the `dotenv_*`
+//! functions pick them up. Because the current directory and environment are shared by the whole
+//! process, tests using these helpers must not run concurrently, e.g. by marking them with
+//! `#[serial]` from the [serial_test](https://crates.io/crates/serial_test) crate.
+//!
+//! # Examples
+//! ```no_run
+//! use dotenv_vault::test_utils::{known_test_key, known_test_vault_entry, with_vault};
+//!
+//! with_vault(known_test_key(), &[known_test_vault_entry()], || {
+//!     dotenv_vault::dotenv().unwrap();
+//!     assert_eq!(std::env::var("ALPHA").unwrap(), "omega");
+//! });
+//! ```
+
+use std::{
+    collections::HashMap,
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// A dotenv key that decrypts [`known_test_vault_entry`]
+pub fn known_test_key() -> &'static str {
+    "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production"
+}
+
+/// A `(name, ciphertext)` *.env.vault* entry that [`known_test_key`] decrypts to `ALPHA="omega"`
+/// and `BETA="gamma"`
+pub fn known_test_vault_entry() -> (&'static str, &'static str) {
+    (
+        "DOTENV_VAULT_PRODUCTION",
+        "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+    )
+}
+
+/// A temporary *.env.vault* file set up by [`setup_vault_env`]
+///
+/// Dropping it restores the current directory and every environment variable to their state
+/// before the setup, including variables loaded from the vault, and deletes the file.
+pub struct TempVaultEnv {
+    dir: TempDir,
+    previous_dir: PathBuf,
+    previous_vars: HashMap<OsString, OsString>,
+}
+
+impl TempVaultEnv {
+    /// The temporary directory, which is the current directory until this is dropped
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The *.env.vault* file in the temporary directory
+    pub fn vault_path(&self) -> PathBuf {
+        self.dir.path().join(".env.vault")
+    }
+}
+
+impl Drop for TempVaultEnv {
+    fn drop(&mut self) {
+        for (key, _) in env::vars_os() {
+            if !self.previous_vars.contains_key(&key) {
+                env::remove_var(key);
+            }
+        }
+        for (key, value) in &self.previous_vars {
+            if env::var_os(key).as_ref() != Some(value) {
+                env::set_var(key, value);
+            }
+        }
+
+        // The previous directory may have been removed in the meantime
+        let _ = env::set_current_dir(&self.previous_dir);
+    }
+}
+
+/// Write a *.env.vault* file with the given `(name, ciphertext)` entries to a new temporary
+/// directory, make it the current directory and set *DOTENV_KEY* to `key`
+///
+/// # Panics
+/// If the temporary directory or *.env.vault* file cannot be created.
+pub fn setup_vault_env(key: &str, ciphertext_pairs: &[(&str, &str)]) -> TempVaultEnv {
+    let dir = tempfile::tempdir().expect("failed to create temporary directory");
+    let contents: Vec<String> = ciphertext_pairs
+        .iter()
+        .map(|(name, ciphertext)| format!("{}=\"{}\"", name, ciphertext))
+        .collect();
+    fs::write(dir.path().join(".env.vault"), contents.join("\n"))
+        .expect("failed to write .env.vault file");
+
+    let vault_env = TempVaultEnv {
+        previous_dir: env::current_dir().expect("failed to read current directory"),
+        previous_vars: env::vars_os().collect(),
+        dir,
+    };
+
+    env::set_current_dir(vault_env.path()).expect("failed to change current directory");
+    env::set_var("DOTENV_KEY", key);
+
+    vault_env
+}
+
+/// Run `f` with a temporary *.env.vault* file set up by [`setup_vault_env`], restoring the
+/// previous state afterwards even if `f` panics
+pub fn with_vault<F>(key: &str, ciphertext_pairs: &[(&str, &str)], f: F)
+where
+    F: FnOnce(),
+{
+    let _vault_env = setup_vault_env(key, ciphertext_pairs);
+    f();
+}
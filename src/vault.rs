@@ -1,333 +1,5405 @@
+use super::env_map::EnvMap;
 use super::errors::{Error, Result};
-use super::log::{info, warn};
+use super::interpolate::{escape, interpolate};
+use super::log::{info, warn, LogLevel};
+use super::vault_config::{KeyOrder, VaultConfig};
 
-use std::{env, path::PathBuf};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    env,
+    ffi::OsString,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Length of the salt that prefixes entries for passphrase keys
+const SALT_LEN: usize = 16;
+
+/// PBKDF2 iterations for passphrase keys without an `iterations` parameter
+const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Variables that [`Vault::load_into_process_env_guarded`] refuses to set by default, because
+/// they change how the process or its children load code and find executables
+pub const DEFAULT_BLOCKLIST: &[&str] = &[
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "LD_AUDIT",
+    "DYLD_INSERT_LIBRARIES",
+    "DYLD_LIBRARY_PATH",
+    "DYLD_FRAMEWORK_PATH",
+    "PATH",
+    "IFS",
+    "BASH_ENV",
+    "ENV",
+    "PROMPT_COMMAND",
+    "NODE_OPTIONS",
+    "PYTHONPATH",
+    "PYTHONSTARTUP",
+    "PERL5OPT",
+    "RUBYOPT",
+];
+
+/// How the entries of a dotenv key are encrypted, from its query parameters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct KeyOptions {
+    /// Whether the plaintext is zstd compressed
+    compressed: bool,
+
+    /// PBKDF2 iterations if the key is a passphrase
+    pbkdf2_iterations: Option<u32>,
+}
+
+/// A vault entry to try decrypting, as `(key, entry, ciphertext, options)`
+type Candidate<T = String> = (String, String, T, KeyOptions);
+
+/// Restores the given variables to their previous values when dropped
+struct EnvRestore(Vec<(String, Option<OsString>)>);
+
+impl Drop for EnvRestore {
+    fn drop(&mut self) {
+        for (key, value) in &self.0 {
+            match value {
+                Some(value) => env::set_var(key, value),
+                None => env::remove_var(key),
+            }
+        }
+    }
+}
+
+/// Parse the value of the variable `key` into `T`
+pub(crate) fn parse_value<T>(key: &str, value: Option<String>) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = value.ok_or_else(|| Error::RequiredVariableMissing(key.to_string()))?;
+
+    value.parse().map_err(|err: T::Err| Error::ParseValueError {
+        key: key.to_string(),
+        source: err.to_string(),
+    })
+}
+
+/// Strip one layer of matching `"` or `'` quotes from a value
+fn strip_outer_quotes(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+
+    value
+}
+
+/// A variable that differs between two vaults
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultDiff {
+    /// Variable name
+    pub key: String,
+
+    /// Value in the left vault, or `None` if it is missing there
+    pub left: Option<String>,
+
+    /// Value in the right vault, or `None` if it is missing there
+    pub right: Option<String>,
+}
+
+/// An access to the *.env.vault* file, recorded when [`VaultConfig::audit`] is enabled
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessRecord {
+    /// When the access finished
+    pub timestamp: SystemTime,
+
+    /// The operation that accessed the vault, such as `"load"` or `"to_map"`
+    pub operation: &'static str,
+
+    /// Name of the environment that was accessed, or an empty string if unknown
+    pub environment: String,
+
+    /// Whether the operation succeeded
+    pub success: bool,
+
+    /// Fingerprint of the key that decrypted the vault, as returned by
+    /// [`Vault::key_fingerprint`], or `None` if the operation failed
+    pub key_fingerprint: Option<String>,
+
+    /// `key_id` parameter of the key that decrypted the vault, as returned by [`Vault::key_id`],
+    /// or `None` if the key has none or the operation failed
+    pub key_id: Option<String>,
+}
+
+/// Metadata about decrypting a vault, returned by [`Vault::stats`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultStats {
+    /// The *.env.vault* file that was decrypted, or `None` if it was held in memory or a regular
+    /// *.env* file was read instead
+    pub path: Option<PathBuf>,
+
+    /// Name of the environment that was decrypted, or `None` if a regular *.env* file was read
+    /// instead
+    pub environment: Option<String>,
+
+    /// Number of variables read
+    pub variable_count: usize,
+
+    /// Time taken to locate and decrypt the vault, or to read the regular *.env* file
+    pub decryption_duration: Duration,
+
+    /// Whether a regular *.env* file was read because no *.env.vault* file or key was found
+    pub fallback_used: bool,
+}
+
+/// The outcome of the startup checks run by [`Vault::preflight_check`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreflightReport {
+    /// Whether a dotenv key was found and every comma-separated key in it is well-formed
+    pub key_valid: bool,
+
+    /// Whether the *.env.vault* file exists or its contents are held in memory
+    pub vault_found: bool,
+
+    /// Whether the *.env.vault* file cannot be modified by other users, always `true` for
+    /// contents held in memory and on platforms without Unix permissions
+    pub permissions_ok: bool,
+
+    /// Sorted lowercase names of the environments the dotenv key decrypts
+    pub environments: Vec<String>,
+
+    /// Human-readable descriptions of the problems found
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for VaultStats {
+    /// A one-line summary, e.g. `Loaded 2 variables from production (.env.vault) in 3ms`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let duration = self.decryption_duration.as_millis();
+
+        if self.fallback_used {
+            return write!(
+                f,
+                "Loaded {} variables from .env (fallback) in {}ms",
+                self.variable_count, duration
+            );
+        }
+
+        write!(
+            f,
+            "Loaded {} variables from {} ({}) in {}ms",
+            self.variable_count,
+            self.environment.as_deref().unwrap_or("unknown"),
+            self.path
+                .as_ref()
+                .map_or("memory".into(), |path| path.display().to_string()),
+            duration
+        )
+    }
+}
+
+/// An iterator over the variables of a vault, returned by [`Vault::iter`]
+///
+/// The decrypted contents are held by the iterator and parsed one variable at a time.
+pub struct VaultIter {
+    inner: Box<dyn Iterator<Item = dotenvy::Result<(String, String)>>>,
+}
+
+impl Iterator for VaultIter {
+    type Item = Result<(String, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|item| item.map_err(Error::from))
+    }
+}
 
 /// Vault data
+#[derive(Clone)]
 pub struct Vault {
     /// Dotenv key
     key: Option<String>,
 
     /// Vault path
     path: Option<PathBuf>,
+
+    /// Vault contents held in memory, used instead of reading `path`
+    contents: Option<Vec<u8>>,
+
+    /// Environment name from *DOTENV_ENVIRONMENT*
+    environment: Option<String>,
+
+    /// Environment to decrypt instead of the `environment` parameter of the dotenv key, set by
+    /// [`Vault::new_for_environment`]
+    target_environment: Option<String>,
+
+    /// Vault options
+    config: VaultConfig,
+
+    /// Audit log shared between clones, appended to when `config.audit` is enabled
+    access_log: Arc<Mutex<Vec<AccessRecord>>>,
+
+    /// Fingerprint of the key used for the last successful decryption, shared between clones
+    last_key_fingerprint: Arc<Mutex<Option<String>>>,
+
+    /// `key_id` parameter of the key used for the last successful decryption, shared between
+    /// clones
+    last_key_id: Arc<Mutex<Option<String>>>,
+}
+
+impl Default for Vault {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Vault {
     /// Create a new Vault using the *DOTENV_KEY* environment variable and a *.env.vault* file in
     /// the current directory
     pub fn new() -> Self {
-        let key = env::var("DOTENV_KEY").map_or(None, |key| Some(key.trim().to_string()));
-        let path = env::current_dir().map_or(None, |path| Some(path.join(".env.vault")));
+        Self::new_with_config(VaultConfig::default())
+    }
+
+    /// Create a new Vault like [`Vault::new`] that decrypts the entry of the environment
+    /// `env_name`, e.g. `DOTENV_VAULT_STAGING` for `staging`, whatever the `environment`
+    /// parameter of the dotenv key says
+    pub fn new_for_environment(env_name: impl Into<String>) -> Self {
+        Self {
+            target_environment: Some(env_name.into()),
+            ..Self::new()
+        }
+    }
+
+    /// Create a new Vault like [`Vault::new`], using the given options
+    ///
+    /// The dotenv key is read from the environment variable named by
+    /// [`VaultConfig::key_var_name`], *DOTENV_KEY* by default.
+    ///
+    /// Without *DOTENV_KEY*, the key is fetched from HashiCorp Vault with the `hashicorp-vault`
+    /// feature and all of [`VaultConfig::hc_vault_addr`], [`VaultConfig::hc_vault_token`] and
+    /// [`VaultConfig::hc_vault_path`] set, warning if that fails. Otherwise it is read from the
+    /// macOS Keychain with the `keychain` feature
+    /// and *DOTENV_VAULT_USE_KEYCHAIN=1*, from the Windows Credential Manager with the `wincred`
+    /// feature and *DOTENV_VAULT_USE_WINCRED=1*, or from the Linux Secret Service with the
+    /// `secret-service` feature and *DOTENV_VAULT_USE_SECRET_SERVICE=1*, then from the
+    /// *DOTENV_KEY* file in the Docker secrets directory if [`VaultConfig::docker_secrets_enabled`] or
+    /// [`VaultConfig::docker_secrets_dir`] is set, and otherwise from the closest *.env.keys*
+    /// file, see [`Vault::find_key_in_ancestors`].
+    pub fn new_with_config(config: VaultConfig) -> Self {
+        let key = env::var(&config.key_var_name).map_or(None, |key| Some(key.trim().to_string()));
+        #[cfg(all(feature = "hashicorp-vault", not(target_arch = "wasm32")))]
+        let key = key.or_else(|| Self::hashicorp_key(&config));
+        #[cfg(all(feature = "keychain", target_os = "macos"))]
+        let key = key.or_else(|| Self::keychain_key(&config));
+        #[cfg(all(feature = "wincred", target_os = "windows"))]
+        let key = key.or_else(|| Self::wincred_key(&config));
+        #[cfg(all(feature = "secret-service", target_os = "linux"))]
+        let key = key.or_else(|| Self::secret_service_key(&config));
+        let key = key.or_else(|| Self::docker_secret_key(&config));
+        let key = key.or_else(|| {
+            env::current_dir()
+                .ok()
+                .and_then(|dir| Self::find_key_in_ancestors(&dir))
+        });
+
+        Self::from_key_and_config(key, config)
+    }
+
+    /// Create a new Vault like [`Vault::new_with_config`] with the given dotenv key, without
+    /// looking for a key in the environment or any key store
+    fn from_key_and_config(key: Option<String>, config: VaultConfig) -> Self {
+        Self {
+            key,
+            path: Self::default_path(),
+            contents: None,
+            environment: env::var("DOTENV_ENVIRONMENT").ok(),
+            target_environment: None,
+            config,
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        }
+    }
+
+    /// Read the dotenv key of the current directory from the macOS Keychain, if it is enabled
+    #[cfg(all(feature = "keychain", target_os = "macos"))]
+    fn keychain_key(config: &VaultConfig) -> Option<String> {
+        if !config.keychain {
+            return None;
+        }
+
+        match super::keychain::read_key() {
+            Ok(key) => key,
+            Err(err) => {
+                warn(
+                    config.log_level,
+                    format!("Failed to read DOTENV_KEY from the Keychain: {}", err),
+                );
+                None
+            }
+        }
+    }
+
+    /// Store a dotenv key in the macOS Keychain for the current directory, replacing any
+    /// existing one
+    ///
+    /// The key is saved as a generic password with the service name `dotenv-vault` and the
+    /// current directory as the account name. [`Vault::new`] reads it back when *DOTENV_KEY* is
+    /// not set and *DOTENV_VAULT_USE_KEYCHAIN=1* is, see [`VaultConfig::keychain`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     Vault::save_key_to_keychain(
+    ///         "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production",
+    ///     )?;
+    ///
+    ///     // later, with DOTENV_VAULT_USE_KEYCHAIN=1 and without DOTENV_KEY
+    ///     Vault::new().load()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "keychain", target_os = "macos"))]
+    pub fn save_key_to_keychain(key: &str) -> Result<()> {
+        super::keychain::save_key(key)
+    }
+
+    /// Read the dotenv key of the current directory from the Windows Credential Manager, if it
+    /// is enabled
+    #[cfg(all(feature = "wincred", target_os = "windows"))]
+    fn wincred_key(config: &VaultConfig) -> Option<String> {
+        if !config.wincred {
+            return None;
+        }
+
+        match super::wincred::read_key() {
+            Ok(key) => key,
+            Err(err) => {
+                warn(
+                    config.log_level,
+                    format!(
+                        "Failed to read DOTENV_KEY from the Windows Credential Manager: {}",
+                        err
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    /// Store a dotenv key in the Windows Credential Manager for the current directory,
+    /// replacing any existing one
+    ///
+    /// The key is saved as a generic credential with the target name `dotenv-vault/<cwd>`,
+    /// where `<cwd>` is the current directory. [`Vault::new`] reads it back when *DOTENV_KEY* is
+    /// not set and *DOTENV_VAULT_USE_WINCRED=1* is, see [`VaultConfig::wincred`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     Vault::save_key_to_wincred(
+    ///         "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production",
+    ///     )?;
+    ///
+    ///     // later, with DOTENV_VAULT_USE_WINCRED=1 and without DOTENV_KEY
+    ///     Vault::new().load()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "wincred", target_os = "windows"))]
+    pub fn save_key_to_wincred(key: &str) -> Result<()> {
+        super::wincred::save_key(key)
+    }
+
+    /// Read the dotenv key of the current directory from the Secret Service, if it is enabled
+    #[cfg(all(feature = "secret-service", target_os = "linux"))]
+    fn secret_service_key(config: &VaultConfig) -> Option<String> {
+        if !config.secret_service {
+            return None;
+        }
+
+        match super::libsecret::read_key() {
+            Ok(key) => key,
+            Err(err) => {
+                warn(
+                    config.log_level,
+                    format!("Failed to read DOTENV_KEY from the Secret Service: {}", err),
+                );
+                None
+            }
+        }
+    }
+
+    /// Store a dotenv key in the default collection of the Secret Service, such as GNOME Keyring
+    /// or KWallet, for the current directory, replacing any existing one
+    ///
+    /// The key is saved as an item with the attribute `dotenv_vault_cwd` set to the current
+    /// directory. [`Vault::new`] reads it back when *DOTENV_KEY* is not set and
+    /// *DOTENV_VAULT_USE_SECRET_SERVICE=1* is, see [`VaultConfig::secret_service`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     Vault::save_key_to_secret_service(
+    ///         "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production",
+    ///     )?;
+    ///
+    ///     // later, with DOTENV_VAULT_USE_SECRET_SERVICE=1 and without DOTENV_KEY
+    ///     Vault::new().load()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "secret-service", target_os = "linux"))]
+    pub fn save_key_to_secret_service(key: &str) -> Result<()> {
+        super::libsecret::save_key(key)
+    }
+
+    /// Read the dotenv key from the Docker secrets directory, if it is configured
+    fn docker_secret_key(config: &VaultConfig) -> Option<String> {
+        let path = config.docker_secrets_dir()?.join("DOTENV_KEY");
+        let key = std::fs::read_to_string(path).ok()?;
+        Some(key.trim().to_string()).filter(|key| !key.is_empty())
+    }
+
+    /// Fetch the dotenv key from HashiCorp Vault, if it is configured
+    #[cfg(all(feature = "hashicorp-vault", not(target_arch = "wasm32")))]
+    fn hashicorp_key(config: &VaultConfig) -> Option<String> {
+        let (addr, token, path) = match (
+            &config.hc_vault_addr,
+            &config.hc_vault_token,
+            &config.hc_vault_path,
+        ) {
+            (Some(addr), Some(token), Some(path)) => (addr, token, path),
+            _ => return None,
+        };
+
+        match super::hashicorp::fetch_key(addr, token, path) {
+            Ok(key) => Some(key),
+            Err(err) => {
+                warn(
+                    config.log_level,
+                    format!("Failed to fetch DOTENV_KEY from HashiCorp Vault: {}", err),
+                );
+                None
+            }
+        }
+    }
+
+    /// Create a new Vault like [`Vault::new_with_config`], using the given dotenv key and vault
+    /// path when present
+    pub(crate) fn from_parts(
+        key: Option<&str>,
+        path: Option<PathBuf>,
+        config: VaultConfig,
+    ) -> Self {
+        let mut vault = match key {
+            Some(key) => Self::from_key_and_config(Some(key.trim().to_string()), config),
+            None => Self::new_with_config(config),
+        };
+
+        if path.is_some() {
+            vault.path = path;
+        }
+
+        vault
+    }
+
+    /// Create a new Vault using the given dotenv key and a *.env.vault* file in the current
+    /// directory
+    pub fn from_key(key: &str) -> Self {
+        Self::from_key_and_config(Some(key.trim().to_string()), VaultConfig::default())
+    }
+
+    /// Create a new Vault using the given dotenv key and *.env.vault* file
+    pub fn from_path(path: impl Into<PathBuf>, key: &str) -> Self {
+        Self {
+            path: Some(path.into()),
+            ..Self::from_key(key)
+        }
+    }
+
+    /// Create a new Vault using the given dotenv key and the contents of a *.env.vault* file held
+    /// in memory
+    ///
+    /// No file system access is needed to decrypt the vault, which makes this the way to use the
+    /// crate on targets without one such as `wasm32-unknown-unknown`.
+    pub fn from_bytes(contents: impl Into<Vec<u8>>, key: &str) -> Self {
+        Self {
+            key: Some(key.trim().to_string()),
+            path: None,
+            contents: Some(contents.into()),
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        }
+    }
+
+    /// Create a new Vault using the given dotenv key and the contents of a *.env.vault* file
+    /// given as a string, like [`Vault::from_bytes`]
+    ///
+    /// This is convenient in tests, where the vault can be written inline instead of to a
+    /// temporary file.
+    pub fn from_vault_string(content: &str, key_uri: impl Into<String>) -> Self {
+        Self::from_bytes(content, &key_uri.into())
+    }
+
+    /// Skip all log output while locating and loading the *.env.vault* file, regardless of
+    /// *DOTENV_VAULT_LOG_LEVEL*
+    pub fn with_logging_disabled(mut self) -> Self {
+        self.config.log_level = LogLevel::None;
+        self
+    }
+
+    /// Try the comma-separated keys of the dotenv key in the given order, instead of
+    /// [`KeyOrder::DocumentOrder`]
+    pub fn with_key_order(mut self, order: KeyOrder) -> Self {
+        self.config.key_order = order;
+        self
+    }
+
+    /// Look for *.env.vault* file entries named with the given prefix instead of `DOTENV_VAULT_`,
+    /// e.g. `MYAPP_VAULT_PRODUCTION` for the prefix `MYAPP_VAULT_`
+    pub fn with_vault_key_prefix(mut self, prefix: &str) -> Self {
+        self.config.vault_key_prefix = prefix.to_string();
+        self
+    }
+
+    /// Look for *.env.vault* file entries named with the given suffix after the environment, e.g.
+    /// `DOTENV_VAULT_PRODUCTION_APP` for the suffix `_APP`
+    pub fn with_vault_key_suffix(mut self, suffix: &str) -> Self {
+        self.config.vault_key_suffix = Some(suffix.to_string());
+        self
+    }
 
-        Self { key, path }
+    /// Create a new Vault using the given dotenv key and the contents of a *.env.vault* file read
+    /// from `reader`, like [`Vault::from_bytes`]
+    pub fn from_reader(mut reader: impl std::io::Read, key: &str) -> Result<Self> {
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        Ok(Self::from_bytes(contents, key))
     }
 
     /// Load the *.env.vault* file into the environment, or load a regular *.env* file if a *.env.vault* file
     /// cannot be found and parsed
+    ///
+    /// On `wasm32` targets this delegates to [`Vault::load_from_memory`].
+    ///
+    /// With the `opentelemetry` feature, each load is reported as a `dotenv_vault.load` span.
+    /// With the `metrics` feature, each load increments the `dotenv_vault.load.attempts` counter
+    /// and either `dotenv_vault.load.success` or `dotenv_vault.load.error`, labelled with the
+    /// `error_code`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip(self),
+            fields(
+                vault.path = ?self.path,
+                vault.environment = ?self.environment(),
+                vault.key_count = tracing::field::Empty,
+            )
+        )
+    )]
     pub fn load(&self) -> Result<()> {
-        match self.find()? {
-            Some(vault) => {
-                dotenvy::from_read(&vault[..])?;
-            }
-            None => {
-                dotenvy::dotenv()?;
+        #[cfg(feature = "opentelemetry")]
+        let started = std::time::SystemTime::now();
+        #[cfg(feature = "metrics")]
+        metrics::counter!("dotenv_vault.load.attempts").increment(1);
+
+        let result = self.load_vault();
+
+        #[cfg(feature = "metrics")]
+        super::telemetry::count_load(&result);
+
+        self.audit("load", result.is_ok());
+
+        #[cfg(feature = "opentelemetry")]
+        super::telemetry::report_load(
+            started,
+            self.path
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            self.environment().unwrap_or_default(),
+            result.is_ok(),
+        );
+
+        result
+    }
+
+    fn load_vault(&self) -> Result<()> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.load_from_memory()
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            match self.find()? {
+                Some(vault) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::Span::current().record(
+                        "vault.key_count",
+                        dotenvy::from_read_iter(&vault[..]).count(),
+                    );
+                    dotenvy::from_read(&vault[..])?;
+                }
+                None => {
+                    dotenvy::dotenv()?;
+                }
             }
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!("loaded vault");
+            Ok(())
         }
+    }
 
-        Ok(())
+    /// Load the entry of the environment `env_name` from the *.env.vault* file into the
+    /// environment, only setting variables that are not already present
+    ///
+    /// Unlike [`Vault::load`], the environment is not read from the dotenv key, which may be a
+    /// full dotenv key uri or only the raw hex key, e.g. from a secrets manager. There is no
+    /// fallback to a regular *.env* file.
+    ///
+    /// # Arguments
+    /// - `env_name` - The environment name, e.g. `production` for the `DOTENV_VAULT_PRODUCTION`
+    ///   entry
+    pub fn load_environment(&self, env_name: &str) -> Result<()> {
+        let result = self.decrypt_environment(env_name).and_then(|decrypted| {
+            dotenvy::from_read(&self.normalize(decrypted)?[..])?;
+            Ok(())
+        });
+
+        self.audit("load_environment", result.is_ok());
+        result
     }
 
-    /// Load the .env.vault file into the environment, or load a regular *.env* file if a .env.vault file
-    /// cannot be found and parsed, overriding any existing values in the environment
-    pub fn load_override(&self) -> Result<()> {
-        match &self.find()? {
-            Some(vault) => {
-                dotenvy::from_read_override(&vault[..])?;
-            }
+    /// Decrypt the entry of the environment `env_name` with the raw key of each dotenv key,
+    /// honoring the `compress` and `kdf` parameters of dotenv key uris
+    fn decrypt_environment(&self, env_name: &str) -> Result<Vec<u8>> {
+        let keys = match self.key.as_ref() {
+            Some(key) => key,
+            None => return Err(Error::KeyNotFound),
+        };
+
+        let entry = self.entry_name(env_name);
+        let entries = self.entries()?;
+        let ciphertext = match entries.iter().find(|(name, _)| name == &entry) {
+            Some((_, ciphertext)) => ciphertext.clone(),
             None => {
-                dotenvy::dotenv_override()?;
+                return Err(Error::EnvironmentNotFound {
+                    requested: entry,
+                    available: self.available_environments(&entries),
+                })
+            }
+        };
+
+        for key in self.ordered_keys(keys) {
+            let raw_key = match Self::raw_key(key) {
+                Ok(raw_key) => raw_key,
+                Err(_) => continue,
+            };
+            let options = if key.contains("://") {
+                match Self::key_options(key) {
+                    Ok(options) => options,
+                    Err(_) => continue,
+                }
+            } else {
+                KeyOptions::default()
+            };
+
+            if let Ok(decrypted) = self.decrypt_with_options(ciphertext.clone(), &raw_key, options)
+            {
+                self.record_key(&raw_key, Self::key_id(key).ok().flatten());
+                return Self::decompress(decrypted, options.compressed);
             }
         }
 
-        Ok(())
+        Err(Error::InvalidKey)
     }
 
-    /// Find and parse a *.env.vault* file
+    /// Load the *.env.vault* file into the environment like [`Vault::load`] without blocking the
+    /// async runtime, fetching it from S3 when [`VaultConfig::s3_bucket`] and
+    /// [`VaultConfig::s3_key`] are set and reading it on tokio's blocking thread pool otherwise
     ///
-    /// # Returns
-    /// A result containing an `Option<Vec<u8>>` for the decrypted vault contents.
+    /// Use this instead of [`Vault::load`] from within an async runtime.
     ///
-    /// If the dotenv key or vault file is missing it returns None, indicating a fallback to a
-    /// regular .env file.
-    fn find(&self) -> Result<Option<Vec<u8>>> {
-        if self.key.is_none() {
-            if !cfg!(debug_assertions) {
-                warn("You are using dotenv-vault in a production environment, but you haven't set DOTENV_KEY. Did you forget? Run 'npx dotenv-vault keys' to view your DOTENV_KEY.");
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// async fn load() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     Vault::new().load_async().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+    pub async fn load_async(&self) -> Result<()> {
+        #[cfg(feature = "s3")]
+        if let Some((bucket, key)) = self.s3_object() {
+            let contents = super::s3::fetch_async(bucket, key).await?;
+            return self.with_contents(contents).load();
+        }
+
+        let vault = self.clone();
+        tokio::task::spawn_blocking(move || vault.load())
+            .await
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?
+    }
+
+    /// Load the *.env.vault* file like [`Vault::load_async`], failing with
+    /// [`Error::LoadTimeout`] if it takes longer than `duration`
+    ///
+    /// After a timeout, a *.env.vault* file that is still being read from disk may be loaded into
+    /// the environment later, once the read completes.
+    #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+    pub async fn load_async_with_timeout(&self, duration: Duration) -> Result<()> {
+        tokio::time::timeout(duration, self.load_async())
+            .await
+            .map_err(|_| Error::LoadTimeout(duration))?
+    }
+
+    /// Load the *.env.vault* file into the environment, or load a regular *.env* file if a
+    /// *.env.vault* file cannot be found and parsed, only setting variables that are not already
+    /// present in the environment
+    ///
+    /// Variables that are already set keep their current value, even if the vault holds a
+    /// different one. This is the same behavior as [`Vault::load`] and [`dotenv`](crate::dotenv).
+    pub fn load_additive(&self) -> Result<()> {
+        self.load()
+    }
+
+    /// Load the *.env.vault* file, or a regular *.env* file if a *.env.vault* file cannot be found
+    /// and parsed, into the environment after passing each variable through `f`
+    ///
+    /// `f` receives each `(key, value)` pair and returns the `(key, value)` pair to set, or
+    /// `None` to skip the variable. Returned variables override any existing values in the
+    /// environment.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     Vault::new().apply(|key, value| {
+    ///         key.starts_with("APP_").then(|| (key.to_string(), value.trim().to_string()))
+    ///     })?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn apply(&self, f: impl Fn(&str, &str) -> Option<(String, String)>) -> Result<()> {
+        for (key, value) in self.to_map()? {
+            if let Some((key, value)) = f(&key, &value) {
+                env::set_var(key, value);
             }
-            return Ok(None);
         }
 
-        if self.path.as_ref().map_or(false, |path| path.exists()) {
-            info("Loading env from encrypted .env.vault");
-            let vault = self.parse()?;
-            return Ok(Some(vault));
+        Ok(())
+    }
+
+    /// Load the .env.vault file into the environment, or load a regular *.env* file if a .env.vault file
+    /// cannot be found and parsed, overriding any existing values in the environment
+    ///
+    /// On `wasm32` targets only the in-memory contents given to [`Vault::from_bytes`] are loaded.
+    pub fn load_override(&self) -> Result<()> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            dotenvy::from_read_override(&self.parse_memory()?[..])?;
+            Ok(())
         }
 
-        warn("You set a DOTENV_KEY but you are missing a .env.vault file. Did you forget to build it? Run 'npx dotenv-vault build'.");
-        Ok(None)
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            match &self.find()? {
+                Some(vault) => {
+                    dotenvy::from_read_override(&vault[..])?;
+                }
+                None => {
+                    dotenvy::dotenv_override()?;
+                }
+            }
+
+            Ok(())
+        }
     }
 
-    /// Decrypt the contents of the *.env.vault* file using AES-256-GCM
+    /// Load the *.env.vault* file into the environment like [`Vault::load`], calling `on_var` with
+    /// the name and value of each variable before it is set
     ///
-    /// # Arguments
-    /// - `encrypted` - The encrypted vault string
-    /// - `key` - The decryption key
-    fn decrypt(&self, encrypted: String, key: String) -> Result<Vec<u8>> {
-        use aes_gcm::{
-            aead::{consts::U12, Aead, KeyInit},
-            Aes256Gcm, Key, Nonce,
+    /// Variables are passed to `on_var` as they are parsed, without collecting them into a map.
+    /// Like [`Vault::load`], variables that are already set in the environment keep their value,
+    /// but `on_var` is still called for them.
+    pub fn load_with_callback(&self, on_var: impl Fn(&str, &str)) -> Result<()> {
+        let apply = |item: dotenvy::Result<(String, String)>| -> Result<()> {
+            let (key, value) = item?;
+            on_var(&key, &value);
+            if env::var_os(&key).is_none() {
+                env::set_var(key, value);
+            }
+            Ok(())
         };
-        use base64::{engine::general_purpose, Engine as _};
 
-        let key_len = key.len();
-        if key_len < 64 {
-            return Err(Error::InvalidKey);
+        #[cfg(target_arch = "wasm32")]
+        {
+            dotenvy::from_read_iter(&self.parse_memory()?[..]).try_for_each(apply)
         }
-        let key = key[key.len() - 64..].to_string();
-        let key = hex::decode(key)?;
-        let ciphertext = general_purpose::STANDARD.decode(encrypted)?;
 
-        let nonce = &ciphertext[0..12];
-        let ciphertext = &ciphertext[12..];
-
-        let key = Key::<Aes256Gcm>::from_slice(&key);
-        let cipher = Aes256Gcm::new(key);
-        let nonce = Nonce::<U12>::from_slice(nonce);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            match self.find()? {
+                Some(vault) => dotenvy::from_read_iter(&vault[..]).try_for_each(apply),
+                None => dotenvy::dotenv_iter()?.try_for_each(apply),
+            }
+        }
+    }
 
-        let plaintext = cipher.decrypt(nonce, ciphertext)?;
+    /// Load the *.env.vault* file into the environment like [`Vault::load`], only setting the
+    /// variables whose names `predicate` returns `true` for
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     let testing = std::env::var_os("APP_TEST").is_some();
+    ///     Vault::new().load_with_env_filter(|key| !(testing && key == "DATABASE_URL"))?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn load_with_env_filter(&self, predicate: impl Fn(&str) -> bool) -> Result<()> {
+        for variable in self.iter()? {
+            let (key, value) = variable?;
+            if predicate(&key) && env::var_os(&key).is_none() {
+                env::set_var(key, value);
+            }
+        }
 
-        Ok(plaintext)
+        Ok(())
     }
 
-    /// Parse the dotenv key uri into a key and environment
+    /// Load the *.env.vault* file into the environment like [`Vault::load`], refusing to set any
+    /// variable named in `blocklist`
     ///
-    /// # Arguments
-    /// - `dotenv_key` - The dotenv key uri
+    /// If the vault contains a blocked variable, [`Error::BlockedVariableName`] is returned and no
+    /// variable is set. Use [`DEFAULT_BLOCKLIST`] to guard against variables such as `LD_PRELOAD`
+    /// or `PATH` that a tampered vault could use to run arbitrary code.
     ///
-    /// # Returns
-    /// A `Result` containing a tuple of `(key, environment)`
-    fn instructions(&self, dotenv_key: &str) -> Result<(String, String)> {
-        let url = url::Url::parse(dotenv_key)?;
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::{Vault, DEFAULT_BLOCKLIST};
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     Vault::new().load_into_process_env_guarded(DEFAULT_BLOCKLIST)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn load_into_process_env_guarded(&self, blocklist: &[&str]) -> Result<()> {
+        let variables = self.iter()?.collect::<Result<Vec<_>>>()?;
+
+        if let Some((key, _)) = variables
+            .iter()
+            .find(|(key, _)| blocklist.contains(&key.as_str()))
+        {
+            return Err(Error::BlockedVariableName(key.clone()));
+        }
+
+        for (key, value) in variables {
+            if env::var_os(&key).is_none() {
+                env::set_var(key, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load the *.env.vault* file into the environment like [`Vault::load`], then set every
+    /// variable in `overrides`, replacing any existing values
+    ///
+    /// This layers the configuration as vault < environment < `overrides`, where `overrides`
+    /// typically holds values from command line flags. Nothing from `overrides` is set if
+    /// loading fails.
+    pub fn load_with_priority(&self, overrides: &HashMap<String, String>) -> Result<()> {
+        self.load()?;
+
+        for (key, value) in overrides {
+            env::set_var(key, value);
+        }
+
+        Ok(())
+    }
+
+    /// Load this vault and then `other` into the environment like [`Vault::load`], so variables
+    /// that are already set keep their value and this vault takes precedence over `other`
+    ///
+    /// Use this to combine a service-specific vault with a shared one, e.g.
+    /// `service.load_merge(&shared)`. Nothing from `other` is set if loading this vault fails.
+    pub fn load_merge(&self, other: &Vault) -> Result<()> {
+        self.load()?;
+        other.load()
+    }
+
+    /// Load this vault and then `other` into the environment like [`Vault::load_override`], so
+    /// `other` takes precedence over this vault and both override existing values
+    pub fn load_merge_override(&self, other: &Vault) -> Result<()> {
+        self.load_override()?;
+        other.load_override()
+    }
+
+    /// Load the *.env.vault* file into the environment like [`Vault::load`] while `f` runs, then
+    /// restore the variables of the vault to their previous values
+    ///
+    /// Variables that were unset before are removed again, even if `f` panics. Like
+    /// [`Vault::reload`], this is racy if other threads read the environment meanwhile.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     let url = Vault::new().load_into_scope(|| std::env::var("DATABASE_URL"))?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn load_into_scope<T>(&self, f: impl FnOnce() -> T) -> Result<T> {
+        // Decrypt once, so the restored variables are exactly the ones that are set
+        let map = EnvMap::from(self.to_map()?);
+        let _restore = EnvRestore(
+            map.keys()
+                .map(|key| (key.clone(), env::var_os(key)))
+                .collect(),
+        );
+
+        map.apply()?;
+        Ok(f())
+    }
+
+    /// Load the *.env.vault* file into the environment like [`Vault::load`], setting `defaults`
+    /// instead if neither a *.env.vault* nor a regular *.env* file is found
+    ///
+    /// Like [`Vault::load`], defaults that are already set in the environment keep their value.
+    /// Any other error, such as a vault that cannot be decrypted, is still returned.
+    pub fn load_or_default(&self, defaults: HashMap<String, String>) -> Result<()> {
+        match self.try_load() {
+            Some(result) => result,
+            None => {
+                for (key, value) in defaults {
+                    if env::var_os(&key).is_none() {
+                        env::set_var(key, value);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Load the *.env.vault* file into the environment like [`Vault::load`], returning `None`
+    /// if neither a *.env.vault* nor a regular *.env* file is found and nothing was loaded
+    ///
+    /// `Some` holds the result of loading whichever file was found, so `Some(Err(_))` means the
+    /// vault could not be decrypted or the *.env* file could not be parsed.
+    pub fn try_load(&self) -> Option<Result<()>> {
+        match self.load() {
+            Err(Error::DotenvyError(err)) if err.not_found() => None,
+            result => Some(result),
+        }
+    }
+
+    /// Load the *.env.vault* file into the environment, or load a regular *.env* file if a
+    /// *.env.vault* file cannot be found and parsed, failing if any variable is already set to a
+    /// different value
+    ///
+    /// Nothing is loaded when there is a conflict. The conflicting names are returned in
+    /// [`Error::ConflictingVariables`].
+    pub fn load_strict(&self) -> Result<()> {
+        let map = self.to_map()?;
+        let conflicts = Self::conflicts(&map);
+
+        if !conflicts.is_empty() {
+            return Err(Error::ConflictingVariables(conflicts));
+        }
+
+        for (key, value) in map {
+            env::set_var(key, value);
+        }
+
+        Ok(())
+    }
+
+    /// Get the sorted names of the variables that [`Vault::load_strict`] would reject because
+    /// they are already set in the environment to a different value, without modifying the
+    /// environment
+    pub fn load_check(&self) -> Result<Vec<String>> {
+        Ok(Self::conflicts(&self.to_map()?))
+    }
+
+    /// Read and decrypt the *.env.vault* file again, or the regular *.env* file it falls back to,
+    /// overriding any existing values in the environment
+    ///
+    /// This is meant for long-running services that reload their configuration, e.g. on
+    /// `SIGHUP`. Reloading is inherently racy: other threads may observe a mix of old and new
+    /// values while variables are being set, so callers should synchronize access externally.
+    pub fn reload(&self) -> Result<()> {
+        self.load_override()
+    }
+
+    /// Load the in-memory contents given to [`Vault::from_bytes`] into the environment, without
+    /// falling back to a regular *.env* file
+    pub fn load_from_memory(&self) -> Result<()> {
+        dotenvy::from_read(&self.parse_memory()?[..])?;
+        Ok(())
+    }
+
+    /// Decrypt the *.env.vault* file, or read a regular *.env* file if a *.env.vault* file cannot
+    /// be found and parsed, into a map without modifying the environment
+    pub fn to_map(&self) -> Result<HashMap<String, String>> {
+        let result = self.find().and_then(|vault| match vault {
+            Some(vault) => Ok(dotenvy::from_read_iter(&vault[..]).collect::<dotenvy::Result<_>>()?),
+            None => Self::fallback_map(),
+        });
+
+        self.audit("to_map", result.is_ok());
+        result
+    }
+
+    /// Decrypt the *.env.vault* file, or read a regular *.env* file if a *.env.vault* file cannot
+    /// be found and parsed, into an iterator over its variables without modifying the
+    /// environment
+    ///
+    /// Unlike [`Vault::into_env_map`], the vault can still be used afterwards.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     let vault = Vault::new();
+    ///     for variable in vault.iter()? {
+    ///         let (key, _) = variable?;
+    ///         println!("{}", key);
+    ///     }
+    ///     vault.load()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn iter(&self) -> Result<VaultIter> {
+        let result = self.find().and_then(|vault| match vault {
+            Some(vault) => Ok(VaultIter {
+                inner: Box::new(dotenvy::from_read_iter(std::io::Cursor::new(vault))),
+            }),
+            None => Self::fallback_iter(),
+        });
+
+        self.audit("iter", result.is_ok());
+        result
+    }
+
+    /// Iterate over the variable names of the vault like [`Vault::iter`], dropping each value as
+    /// soon as it is parsed
+    pub fn keys_iter(&self) -> Result<impl Iterator<Item = Result<String>>> {
+        Ok(self.iter()?.map(|variable| variable.map(|(key, _)| key)))
+    }
+
+    /// Iterate over the variable values of the vault like [`Vault::iter`]
+    pub fn values_iter(&self) -> Result<impl Iterator<Item = Result<String>>> {
+        Ok(self
+            .iter()?
+            .map(|variable| variable.map(|(_, value)| value)))
+    }
+
+    /// Decrypt the *.env.vault* file like [`Vault::to_map`], consuming the vault, into an
+    /// [`EnvMap`] that can be applied to the environment later
+    pub fn into_env_map(self) -> Result<EnvMap> {
+        Ok(self.to_map()?.into())
+    }
+
+    /// Decrypt the *.env.vault* file like [`Vault::to_map`], without modifying the environment,
+    /// and report how it went
+    pub fn stats(&self) -> Result<VaultStats> {
+        let start = Instant::now();
+        let (map, fallback_used) = match self.find()? {
+            Some(vault) => (
+                dotenvy::from_read_iter(&vault[..]).collect::<dotenvy::Result<HashMap<_, _>>>()?,
+                false,
+            ),
+            None => (Self::fallback_map()?, true),
+        };
+        let decryption_duration = start.elapsed();
+
+        if fallback_used {
+            return Ok(VaultStats {
+                path: None,
+                environment: None,
+                variable_count: map.len(),
+                decryption_duration,
+                fallback_used,
+            });
+        }
+
+        Ok(VaultStats {
+            path: self.decrypted_path(),
+            environment: self.environment(),
+            variable_count: map.len(),
+            decryption_duration,
+            fallback_used,
+        })
+    }
+
+    /// The *.env.vault* file read from disk when decrypting
+    #[cfg(not(target_arch = "wasm32"))]
+    fn decrypted_path(&self) -> Option<PathBuf> {
+        if self.contents.is_some() {
+            return None;
+        }
+
+        self.discovered_path().or_else(|| self.vault_path())
+    }
+
+    /// There is no file system to read the *.env.vault* file from
+    #[cfg(target_arch = "wasm32")]
+    fn decrypted_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Get a snapshot of the accesses recorded by [`Vault::load`] and [`Vault::to_map`] when
+    /// [`VaultConfig::audit`] is enabled
+    ///
+    /// Clones of a vault share the same log.
+    pub fn access_log(&self) -> Vec<AccessRecord> {
+        self.access_log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get the fingerprint of the key used for the last successful decryption, as returned by
+    /// [`Vault::key_fingerprint`]
+    ///
+    /// Clones of a vault share the same fingerprint.
+    pub fn last_key_fingerprint(&self) -> Option<String> {
+        self.last_key_fingerprint
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get the `key_id` parameter of the key used for the last successful decryption, as
+    /// returned by [`Vault::key_id`]
+    ///
+    /// Unlike [`Vault::last_key_fingerprint`], the id is chosen by whoever issues the key, e.g.
+    /// `dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&key_id=2024-06`,
+    /// so operators can tell which key version decrypted the vault while rotating keys. Clones
+    /// of a vault share the same id.
+    pub fn last_key_id(&self) -> Option<String> {
+        self.last_key_id
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Read the `key_id` parameter of a dotenv key uri
+    ///
+    /// # Arguments
+    /// - `dotenv_key` - A dotenv key uri, or only the raw key, which never has an id
+    ///
+    /// # Returns
+    /// A `Result` containing the id, or `None` if the key has no `key_id` parameter
+    pub fn key_id(dotenv_key: &str) -> Result<Option<String>> {
+        let dotenv_key = dotenv_key.trim();
+        if !dotenv_key.contains("://") {
+            return Ok(None);
+        }
+
+        let url = url::Url::parse(dotenv_key)?;
+        Ok(url
+            .query_pairs()
+            .find(|(k, _)| k == "key_id")
+            .map(|(_, key_id)| key_id.into_owned()))
+    }
+
+    /// Remember the key used for a successful decryption
+    fn record_key(&self, raw_key: &str, key_id: Option<String>) {
+        *self
+            .last_key_fingerprint
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Self::key_fingerprint(raw_key).ok();
+        *self
+            .last_key_id
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = key_id;
+    }
+
+    /// Identify a key without exposing it, as the first 16 hex characters of the SHA-256 hash of
+    /// its last 64 hex characters
+    ///
+    /// # Arguments
+    /// - `key` - A dotenv key uri, or the key part of one
+    pub fn key_fingerprint(key: &str) -> Result<String> {
+        use sha2::{Digest, Sha256};
+
+        let key = key.trim();
+        let key = match url::Url::parse(key) {
+            Ok(url) if url.scheme() == "dotenv" => match url.password() {
+                Some(key) => key.to_string(),
+                None => return Err(Error::MissingKey),
+            },
+            _ => key.to_string(),
+        };
+
+        let key = match key.len().checked_sub(64).and_then(|start| key.get(start..)) {
+            Some(key) => key,
+            None => return Err(Error::InvalidKey),
+        };
+        hex::decode(key)?;
+
+        let hash = Sha256::digest(key.as_bytes());
+        Ok(hex::encode(&hash[..8]))
+    }
+
+    fn audit(&self, operation: &'static str, success: bool) {
+        if !self.config.audit {
+            return;
+        }
+
+        let record = AccessRecord {
+            timestamp: SystemTime::now(),
+            operation,
+            environment: self.environment().unwrap_or_default(),
+            success,
+            key_fingerprint: if success {
+                self.last_key_fingerprint()
+            } else {
+                None
+            },
+            key_id: if success { self.last_key_id() } else { None },
+        };
+        self.access_log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(record);
+    }
+
+    /// Decrypt the *.env.vault* file, or read a regular *.env* file if a *.env.vault* file cannot
+    /// be found and parsed, into `map` without modifying the environment
+    ///
+    /// Existing entries in `map` are kept, like existing variables are kept by [`Vault::load`].
+    pub fn load_into(&self, map: &mut HashMap<String, String>) -> Result<()> {
+        for (key, value) in self.to_map()? {
+            map.entry(key).or_insert(value);
+        }
+
+        Ok(())
+    }
+
+    /// Decrypt the *.env.vault* file, or read a regular *.env* file if a *.env.vault* file cannot
+    /// be found and parsed, into `map` without modifying the environment, overriding any existing
+    /// entries in `map`
+    pub fn load_override_into(&self, map: &mut HashMap<String, String>) -> Result<()> {
+        map.extend(self.to_map()?);
+        Ok(())
+    }
+
+    /// Count the variables in the *.env.vault* file, or a regular *.env* file if a *.env.vault*
+    /// file cannot be found and parsed, without modifying the environment
+    pub fn len(&self) -> Result<usize> {
+        Ok(self.to_map()?.len())
+    }
+
+    /// Check whether the *.env.vault* file, or a regular *.env* file if a *.env.vault* file
+    /// cannot be found and parsed, has no variables, without modifying the environment
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Get a single variable from the *.env.vault* file, or a regular *.env* file if a *.env.vault*
+    /// file cannot be found and parsed, without modifying the environment
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.to_map()?.remove(key))
+    }
+
+    /// Get a single variable like [`Vault::get`], falling back to the process environment if
+    /// the vault does not define it
+    ///
+    /// Unlike [`Vault::load`] followed by `std::env::var`, neither source is modified.
+    pub fn get_or_env(&self, key: &str) -> Result<Option<String>> {
+        match self.get(key)? {
+            Some(value) => Ok(Some(value)),
+            None => Ok(env::var(key).ok()),
+        }
+    }
+
+    /// Get the variables whose names match a glob `pattern` from the *.env.vault* file, or a
+    /// regular *.env* file if a *.env.vault* file cannot be found and parsed, without modifying
+    /// the environment
+    ///
+    /// In `pattern`, `*` matches any number of characters and `?` matches exactly one, e.g.
+    /// `DB_*` or `APP_*_URL`. A pattern without wildcards matches only the variable of that name.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     let database = Vault::new().get_all_matching("DB_*")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn get_all_matching(&self, pattern: &str) -> Result<HashMap<String, String>> {
+        let pattern = wildmatch::WildMatch::new(pattern);
+        let mut map = self.to_map()?;
+        map.retain(|key, _| pattern.matches(key));
+        Ok(map)
+    }
+
+    /// Get the sorted names of the variables in the *.env.vault* file, without falling back to a
+    /// regular *.env* file or modifying the environment
+    ///
+    /// The *.env.vault* file is located like [`Vault::to_map`] does, including remote backends,
+    /// [`VaultConfig::git_root_discovery`] and [`VaultConfig::xdg_fallback`]. Values are
+    /// discarded as soon as each variable is parsed.
+    pub fn keys(&self) -> Result<Vec<String>> {
+        let decrypted = match self.find()? {
+            Some(decrypted) => decrypted,
+            None if self.key.is_none() => return Err(Error::KeyNotFound),
+            None => return Err(Error::VaultNotFound),
+        };
+        let mut keys = dotenvy::from_read_iter(&decrypted[..])
+            .map(|item| item.map(|(key, _)| key))
+            .collect::<dotenvy::Result<Vec<_>>>()?;
+        keys.sort();
+        keys.dedup();
+        Ok(keys)
+    }
+
+    /// Count the variables in the *.env.vault* file, without falling back to a regular *.env*
+    /// file or modifying the environment
+    pub fn values_count(&self) -> Result<usize> {
+        Ok(self.keys()?.len())
+    }
+
+    /// Get a single variable from the *.env.vault* file, or a regular *.env* file if a *.env.vault*
+    /// file cannot be found and parsed, parsed into `T` without modifying the environment
+    ///
+    /// A missing variable is reported as [`Error::RequiredVariableMissing`], and a value that
+    /// fails to parse as [`Error::ParseValueError`].
+    pub fn get_typed<T>(&self, key: &str) -> Result<T>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        parse_value(key, self.get(key)?)
+    }
+
+    /// Check that the dotenv key decrypts the *.env.vault* file, without falling back to a regular
+    /// *.env* file or modifying the environment
+    ///
+    /// # Returns
+    /// A `Result` containing a tuple of `(environment, variable_count)`, where `environment` is
+    /// the lowercase name of the environment that was decrypted
+    pub fn verify(&self) -> Result<(String, usize)> {
+        if self.key.is_none() {
+            return Err(Error::KeyNotFound);
+        }
+
+        if !self.exists() {
+            return Err(Error::VaultNotFound);
+        }
+
+        let (entry, decrypted) = self.decrypt_entry()?;
+        let vars = dotenvy::from_read_iter(&decrypted[..])
+            .collect::<dotenvy::Result<HashMap<String, String>>>()?;
+        let environment = self.entry_environment(&entry).unwrap_or_default();
+
+        Ok((environment, vars.len()))
+    }
+
+    /// Check that the vault is ready to be loaded, without falling back to a regular *.env* file
+    /// or modifying the environment
+    ///
+    /// Each check is reported in the returned [`PreflightReport`] rather than failing early, so
+    /// all problems are found in one call.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dotenv_vault::Vault;
+    ///
+    /// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ///     let report = Vault::new().preflight_check()?;
+    ///     for warning in &report.warnings {
+    ///         eprintln!("warning: {}", warning);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn preflight_check(&self) -> Result<PreflightReport> {
+        let mut report = PreflightReport {
+            permissions_ok: true,
+            ..PreflightReport::default()
+        };
+
+        match self.key.as_ref() {
+            Some(keys) => {
+                report.key_valid = true;
+                for key in self.ordered_keys(keys) {
+                    if let Err(err) = self
+                        .instructions_multi(key)
+                        .and_then(|_| Self::key_options(key))
+                    {
+                        report.key_valid = false;
+                        report.warnings.push(err.to_string());
+                    }
+                }
+            }
+            None => report.warnings.push(Error::KeyNotFound.to_string()),
+        }
+
+        report.vault_found = self.exists();
+        if !report.vault_found {
+            report.warnings.push(Error::VaultNotFound.to_string());
+            return Ok(report);
+        }
+
+        #[cfg(all(unix, not(target_arch = "wasm32")))]
+        if self.contents.is_none() {
+            if let Some(path) = self.vault_path() {
+                use std::os::unix::fs::PermissionsExt;
+
+                let mode = std::fs::metadata(&path)?.permissions().mode();
+                if mode & 0o002 != 0 {
+                    report.permissions_ok = false;
+                    report.warnings.push(format!(
+                        "{} is writable by other users, run 'chmod o-w {}'",
+                        path.display(),
+                        path.display()
+                    ));
+                }
+            }
+        }
+
+        if !report.key_valid {
+            return Ok(report);
+        }
+
+        match self.load_all_environments() {
+            Ok(environments) => {
+                report.environments = environments.into_keys().collect();
+                report.environments.sort();
+            }
+            Err(err) => report.warnings.push(err.to_string()),
+        }
+
+        if report.environments.is_empty() {
+            report
+                .warnings
+                .push("No environment can be decrypted with your DOTENV_KEY".to_string());
+        }
+
+        Ok(report)
+    }
+
+    /// Decrypt every environment in the *.env.vault* file, without falling back to a regular
+    /// *.env* file or modifying the environment
+    ///
+    /// Each `DOTENV_VAULT_*` entry is decrypted with the first of the comma-separated dotenv keys
+    /// that can decrypt it, regardless of the environments the keys list. Entries that none of
+    /// the keys can decrypt are skipped with a warning.
+    ///
+    /// # Returns
+    /// A `Result` containing the variables of each environment, keyed by environment name such
+    /// as `production`
+    pub fn load_all_environments(&self) -> Result<HashMap<String, HashMap<String, String>>> {
+        let keys: Vec<(String, KeyOptions)> = match self.key.as_ref() {
+            Some(keys) => self
+                .ordered_keys(keys)
+                .into_iter()
+                .filter_map(|key| {
+                    let (k, _) = self.instructions_multi(key).ok()?;
+                    Some((k, Self::key_options(key).ok()?))
+                })
+                .collect(),
+            None => return Err(Error::KeyNotFound),
+        };
+
+        let mut environments = HashMap::new();
+        for (entry, ciphertext) in self.entries()? {
+            let environment = match self.entry_environment(&entry) {
+                Some(environment) => environment,
+                None => continue,
+            };
+
+            let decrypted = keys.iter().find_map(|(key, options)| {
+                let decrypted = self
+                    .decrypt_with_options(ciphertext.clone(), key, *options)
+                    .ok()?;
+                Some(Self::decompress(decrypted, options.compressed))
+            });
+            let decrypted = match decrypted.transpose()? {
+                Some(decrypted) => self.normalize(decrypted)?,
+                None => {
+                    warn(
+                        self.config.log_level,
+                        format!(
+                            "Skipping environment {}, which your DOTENV_KEY cannot decrypt",
+                            environment
+                        ),
+                    );
+                    continue;
+                }
+            };
+
+            let vars = dotenvy::from_read_iter(&decrypted[..]).collect::<dotenvy::Result<_>>()?;
+            environments.insert(environment, vars);
+        }
+
+        Ok(environments)
+    }
+
+    /// Compare the variables of this vault with those of `other`, without modifying the
+    /// environment
+    ///
+    /// # Returns
+    /// A `Result` containing the variables that are missing from either vault or have different
+    /// values, sorted by name
+    pub fn diff(&self, other: &Vault) -> Result<Vec<VaultDiff>> {
+        let left = self.to_map()?;
+        let right = other.to_map()?;
+
+        let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+        let diff = keys
+            .into_iter()
+            .filter(|key| left.get(*key) != right.get(*key))
+            .map(|key| VaultDiff {
+                key: key.clone(),
+                left: left.get(key).cloned(),
+                right: right.get(key).cloned(),
+            })
+            .collect();
+
+        Ok(diff)
+    }
+
+    /// Names of the variables in `map` that are already set to a different value, sorted
+    fn conflicts(map: &HashMap<String, String>) -> Vec<String> {
+        let mut conflicts: Vec<String> = map
+            .iter()
+            .filter(|(key, value)| env::var(key).is_ok_and(|current| &current != *value))
+            .map(|(key, _)| key.clone())
+            .collect();
+        conflicts.sort();
+        conflicts
+    }
+
+    /// Read a regular *.env* file into a map
+    #[cfg(not(target_arch = "wasm32"))]
+    fn fallback_map() -> Result<HashMap<String, String>> {
+        Ok(dotenvy::dotenv_iter()?.collect::<dotenvy::Result<_>>()?)
+    }
+
+    /// There is no regular *.env* file to fall back to without a file system
+    #[cfg(target_arch = "wasm32")]
+    fn fallback_map() -> Result<HashMap<String, String>> {
+        Err(Error::VaultNotFound)
+    }
+
+    /// Iterate over the regular *.env* file when there is no *.env.vault* file or key
+    #[cfg(not(target_arch = "wasm32"))]
+    fn fallback_iter() -> Result<VaultIter> {
+        Ok(VaultIter {
+            inner: Box::new(dotenvy::dotenv_iter()?),
+        })
+    }
+
+    /// There is no regular *.env* file to fall back to without a file system
+    #[cfg(target_arch = "wasm32")]
+    fn fallback_iter() -> Result<VaultIter> {
+        Err(Error::VaultNotFound)
+    }
+
+    /// Path of the *.env.vault* file in the current directory
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default_path() -> Option<PathBuf> {
+        env::current_dir().map_or(None, |path| Some(path.join(".env.vault")))
+    }
+
+    /// There is no current directory without a file system
+    #[cfg(target_arch = "wasm32")]
+    fn default_path() -> Option<PathBuf> {
+        None
+    }
+
+    /// The *.env.vault* file found by [`VaultConfig::git_root_discovery`] or
+    /// [`VaultConfig::xdg_fallback`] when there is none at `path`
+    #[cfg(not(target_arch = "wasm32"))]
+    fn discovered_path(&self) -> Option<PathBuf> {
+        if self.exists() {
+            return None;
+        }
+
+        if self.config.git_root_discovery {
+            let ancestor = env::current_dir()
+                .ok()
+                .and_then(|dir| Self::find_vault_in_ancestors(&dir));
+            if ancestor.is_some() {
+                return ancestor;
+            }
+        }
+
+        if self.config.xdg_fallback {
+            return Self::xdg_vault_path().filter(|path| path.is_file());
+        }
+
+        None
+    }
+
+    /// Path of the user-level *.env.vault* file, *$XDG_CONFIG_HOME/dotenv-vault/.env.vault*
+    /// (*~/.config/dotenv-vault/.env.vault* by default) on Linux or the platform's config
+    /// directory elsewhere
+    ///
+    /// Returns `None` if there is no config directory; the file itself may not exist.
+    pub fn xdg_vault_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dotenv-vault").join(".env.vault"))
+    }
+
+    /// Find the closest *.env.vault* file in `start` or its ancestors
+    ///
+    /// The search stops at the first directory containing *.git*, so a vault outside of the
+    /// current repository is never used. Returns `None` if no *.env.vault* file is found.
+    pub fn find_vault_in_ancestors(start: &Path) -> Option<PathBuf> {
+        for dir in start.ancestors() {
+            let path = dir.join(".env.vault");
+            if path.is_file() {
+                return Some(path);
+            }
+
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+
+        None
+    }
+
+    /// Find the dotenv key in the closest *.env.keys* file in `start` or its ancestors
+    ///
+    /// The *.env.keys* file holds one key per environment, such as
+    /// `DOTENV_KEY_PRODUCTION="dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production"`.
+    /// The key of the *DOTENV_ENVIRONMENT* environment is returned, or of `development` if it is
+    /// not set. Like [`Vault::find_vault_in_ancestors`], the search stops at the first directory
+    /// containing *.git*. Returns `None` if no *.env.keys* file is found or the closest one has
+    /// no key for the environment.
+    pub fn find_key_in_ancestors(start: &Path) -> Option<String> {
+        let environment =
+            env::var("DOTENV_ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
+        let name = format!("DOTENV_KEY_{}", environment.to_uppercase());
+
+        for dir in start.ancestors() {
+            let path = dir.join(".env.keys");
+            if path.is_file() {
+                return dotenvy::from_path_iter(path)
+                    .ok()?
+                    .filter_map(|item| item.ok())
+                    .find(|(key, _)| key == &name)
+                    .map(|(_, value)| value.trim().to_string())
+                    .filter(|value| !value.is_empty());
+            }
+
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+
+        None
+    }
+
+    /// Check whether the vault contents are held in memory or the *.env.vault* file exists
+    fn exists(&self) -> bool {
+        if self.contents.is_some() {
+            return true;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.vault_path().is_some_and(|path| path.exists()) {
+            return true;
+        }
+
+        false
+    }
+
+    /// Name of the environment being loaded, from [`Vault::new_for_environment`],
+    /// *DOTENV_ENVIRONMENT* or else the `environment` parameter of the first dotenv key
+    fn environment(&self) -> Option<String> {
+        if let Some(environment) = self.target_environment.as_ref() {
+            return Some(environment.clone());
+        }
+
+        if let Some(environment) = self.environment.as_ref() {
+            return Some(environment.clone());
+        }
+
+        let key = self.key.as_ref()?.split(',').next()?;
+        let url = url::Url::parse(key).ok()?;
+        let (_, environment) = url.query_pairs().find(|(k, _)| k == "environment")?;
+        Some(environment.to_string())
+    }
+
+    /// Resolve the vault file to read: a *.env.{environment}.vault* file next to `path` if it
+    /// exists and per-environment files are enabled, otherwise `path` itself
+    #[cfg(not(target_arch = "wasm32"))]
+    fn vault_path(&self) -> Option<PathBuf> {
+        let path = self.path.as_ref()?;
+
+        if self.config.use_per_environment_files {
+            if let Some(environment) = self.environment() {
+                let environment_path = path.with_file_name(format!(".env.{}.vault", environment));
+                if environment_path.exists() {
+                    return Some(environment_path);
+                }
+            }
+        }
+
+        Some(path.clone())
+    }
+
+    /// Deserialize the variables returned by [`Vault::to_map`] into `T`
+    ///
+    /// Variable names are matched case-insensitively against field names, so `ALPHA` populates a
+    /// field named `alpha`.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T>(&self) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(envy::from_iter(self.to_map()?)?)
+    }
+
+    /// Find and parse a *.env.vault* file
+    ///
+    /// # Returns
+    /// A result containing an `Option<Vec<u8>>` for the decrypted vault contents.
+    ///
+    /// If the dotenv key or vault file is missing it returns None, indicating a fallback to a
+    /// regular .env file.
+    fn find(&self) -> Result<Option<Vec<u8>>> {
+        #[cfg(all(feature = "aws-secrets", not(target_arch = "wasm32")))]
+        if let Some(name) = self.config.aws_secret_name.as_deref() {
+            use super::vault_config::AwsSecretMode;
+
+            info(
+                self.config.log_level,
+                "Loading env from AWS Secrets Manager",
+            );
+            let secret = super::aws_secrets::fetch(name)?;
+
+            let mut vault = self.clone();
+            vault.config.aws_secret_name = None;
+            match self.config.aws_secret_mode {
+                AwsSecretMode::Key => vault.key = Some(secret.trim().to_string()),
+                AwsSecretMode::Vault => vault.contents = Some(secret.into_bytes()),
+            }
+            return vault.find();
+        }
+
+        if self.key.is_none() {
+            if !cfg!(debug_assertions) {
+                warn(self.config.log_level, "You are using dotenv-vault in a production environment, but you haven't set DOTENV_KEY. Did you forget? Run 'npx dotenv-vault keys' to view your DOTENV_KEY.");
+            }
+            return Ok(None);
+        }
+
+        #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+        if let Some(url) = self.config.vault_url.as_deref() {
+            info(self.config.log_level, "Loading env from remote .env.vault");
+            let mut response = super::remote::fetch(url, self.config.vault_auth_token.as_deref())?;
+            let mut contents = Vec::new();
+            std::io::Read::read_to_end(&mut response, &mut contents)?;
+
+            // Keep sharing the access log and last key with this vault
+            let mut remote = self.clone();
+            remote.contents = Some(contents);
+            remote.config.vault_url = None;
+            return remote.parse().map(Some);
+        }
+
+        #[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+        if let Some((bucket, key)) = self.s3_object() {
+            info(self.config.log_level, "Loading env from .env.vault in S3");
+            let contents = super::s3::fetch(bucket, key)?;
+            return self.with_contents(contents).parse().map(Some);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = self.discovered_path() {
+            info(
+                self.config.log_level,
+                format!("Loading env from {}", path.display()),
+            );
+            let mut vault = self.clone();
+            vault.path = Some(path);
+            vault.config.git_root_discovery = false;
+            vault.config.xdg_fallback = false;
+            return vault.find();
+        }
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!("vault_find", vault.path = ?self.path).entered();
+        let exists = self.exists();
+        #[cfg(feature = "tracing")]
+        {
+            tracing::debug!(vault.exists = exists, "checked for vault");
+            span.exit();
+        }
+
+        if exists {
+            info(
+                self.config.log_level,
+                "Loading env from encrypted .env.vault",
+            );
+            let vault = self.parse()?;
+            return Ok(Some(vault));
+        }
+
+        warn(self.config.log_level, "You set a DOTENV_KEY but you are missing a .env.vault file. Did you forget to build it? Run 'npx dotenv-vault build'.");
+        Ok(None)
+    }
+
+    /// The S3 bucket and key to fetch the *.env.vault* file from, if both are configured
+    #[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+    fn s3_object(&self) -> Option<(&str, &str)> {
+        match (&self.config.s3_bucket, &self.config.s3_key) {
+            (Some(bucket), Some(key)) => Some((bucket, key)),
+            _ => None,
+        }
+    }
+
+    /// A copy of this vault that reads the given *.env.vault* contents instead of any file or
+    /// remote backend
+    #[cfg(all(feature = "s3", not(target_arch = "wasm32")))]
+    fn with_contents(&self, contents: Vec<u8>) -> Self {
+        let mut vault = self.clone();
+        vault.contents = Some(contents);
+        vault.config.s3_bucket = None;
+        vault.config.s3_key = None;
+        vault
+    }
+
+    /// Decrypt the contents of the *.env.vault* file using AES-256-GCM
+    ///
+    /// # Arguments
+    /// - `encrypted` - The encrypted vault string
+    /// - `key` - The decryption key
+    fn decrypt(&self, encrypted: String, key: String) -> Result<Vec<u8>> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let key_len = key.len();
+        if key_len < 64 || !key.is_char_boundary(key_len - 64) {
+            return Err(Error::InvalidKey);
+        }
+        let key = hex::decode(&key[key_len - 64..])?;
+        let ciphertext = general_purpose::STANDARD.decode(encrypted)?;
+
+        Self::decrypt_bytes(&ciphertext, &key)
+    }
+
+    /// Decrypt a vault entry with the key or passphrase of a dotenv key, as described by its
+    /// options
+    fn decrypt_with_options(
+        &self,
+        encrypted: String,
+        key: &str,
+        options: KeyOptions,
+    ) -> Result<Vec<u8>> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let iterations = match options.pbkdf2_iterations {
+            Some(iterations) => iterations,
+            None => return self.decrypt(encrypted, key.to_string()),
+        };
+
+        let ciphertext = general_purpose::STANDARD.decode(encrypted)?;
+        if ciphertext.len() < SALT_LEN {
+            return Err(Error::MissingSalt);
+        }
+        let (salt, ciphertext) = ciphertext.split_at(SALT_LEN);
+
+        // The passphrase is percent-encoded in the dotenv key uri
+        let passphrase: Vec<u8> = percent_encoding::percent_decode_str(key).collect();
+        let key = Self::derive_key(&passphrase, salt, iterations);
+
+        Self::decrypt_bytes(ciphertext, &key)
+    }
+
+    /// Derive an AES-256 key from a passphrase with PBKDF2-HMAC-SHA256
+    fn derive_key(passphrase: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+        let mut key = [0; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(passphrase, salt, iterations, &mut key);
+        key
+    }
+
+    /// Decrypt a nonce-prefixed AES-256-GCM ciphertext
+    fn decrypt_bytes(ciphertext: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+        use aes_gcm::{
+            aead::{consts::U12, Aead, KeyInit},
+            Aes256Gcm, Key, Nonce,
+        };
+
+        // 12 byte nonce followed by at least the 16 byte authentication tag
+        let min_len = 12 + 16;
+        if ciphertext.len() < min_len {
+            return Err(Error::InvalidCiphertext {
+                actual_len: ciphertext.len(),
+                min_len,
+            });
+        }
+
+        let nonce = &ciphertext[0..12];
+        let ciphertext = &ciphertext[12..];
+
+        let key = Key::<Aes256Gcm>::from_slice(key);
+        let cipher = Aes256Gcm::new(key);
+        let nonce = Nonce::<U12>::from_slice(nonce);
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "vault_decrypt",
+            vault.decrypted_bytes = tracing::field::Empty
+        )
+        .entered();
+        let plaintext = cipher.decrypt(nonce, ciphertext)?;
+        #[cfg(feature = "tracing")]
+        {
+            span.record("vault.decrypted_bytes", plaintext.len());
+            tracing::debug!("decrypted vault entry");
+        }
+
+        Ok(plaintext)
+    }
+
+    /// Encrypt vault contents using AES-256-GCM, in the format read by the *.env.vault* file
+    ///
+    /// A random nonce is generated for every call and prepended to the ciphertext, which is then
+    /// base64 encoded.
+    ///
+    /// # Arguments
+    /// - `plaintext` - The vault contents to encrypt
+    /// - `key` - The encryption key, of which the last 64 characters are used as hex
+    /// - `compress` - Whether to zstd compress the contents before encrypting them, for dotenv
+    ///   keys with `compress=zstd`; this requires the `compress` feature
+    ///
+    /// # Examples
+    /// ```
+    /// use dotenv_vault::Vault;
+    ///
+    /// let ciphertext = Vault::encrypt(
+    ///     b"ALPHA=\"zeta\"",
+    ///     "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00",
+    ///     false,
+    /// )
+    /// .unwrap();
+    /// let vault = Vault::from_bytes(
+    ///     format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext),
+    ///     "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+    /// );
+    /// assert_eq!(vault.get("ALPHA").unwrap().unwrap(), "zeta");
+    /// ```
+    pub fn encrypt(plaintext: &[u8], key: &str, compress: bool) -> Result<String> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let key_len = key.len();
+        if key_len < 64 || !key.is_char_boundary(key_len - 64) {
+            return Err(Error::InvalidKey);
+        }
+        let key = hex::decode(&key[key_len - 64..])?;
+
+        Ok(general_purpose::STANDARD.encode(Self::encrypt_bytes(plaintext, &key, compress)?))
+    }
+
+    /// Encrypt vault contents with a key derived from a passphrase, for dotenv keys with
+    /// `kdf=pbkdf2`
+    ///
+    /// A random 16 byte salt is generated for every call and prepended to the nonce and
+    /// ciphertext, which are then base64 encoded.
+    ///
+    /// # Arguments
+    /// - `plaintext` - The vault contents to encrypt
+    /// - `passphrase` - The passphrase, as it appears percent-decoded in the dotenv key
+    /// - `iterations` - The PBKDF2-HMAC-SHA256 iterations, matching the `iterations` parameter
+    /// - `compress` - Whether to zstd compress the contents before encrypting them
+    ///
+    /// # Examples
+    /// ```
+    /// use dotenv_vault::Vault;
+    ///
+    /// let ciphertext =
+    ///     Vault::encrypt_with_passphrase(b"ALPHA=\"zeta\"", "correct horse", 1000, false).unwrap();
+    /// let vault = Vault::from_bytes(
+    ///     format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext),
+    ///     "dotenv://:correct%20horse@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1000",
+    /// );
+    /// assert_eq!(vault.get("ALPHA").unwrap().unwrap(), "zeta");
+    /// ```
+    pub fn encrypt_with_passphrase(
+        plaintext: &[u8],
+        passphrase: &str,
+        iterations: u32,
+        compress: bool,
+    ) -> Result<String> {
+        Self::encrypt_with_passphrase_bytes(plaintext, passphrase.as_bytes(), iterations, compress)
+    }
+
+    /// Encrypt vault contents with a key derived from the raw bytes of a passphrase, which may
+    /// not be valid UTF-8 once percent-decoded from a dotenv key
+    fn encrypt_with_passphrase_bytes(
+        plaintext: &[u8],
+        passphrase: &[u8],
+        iterations: u32,
+        compress: bool,
+    ) -> Result<String> {
+        use aes_gcm::aead::{rand_core::RngCore, OsRng};
+        use base64::{engine::general_purpose, Engine as _};
+
+        if iterations == 0 {
+            return Err(Error::InvalidKdf("pbkdf2 with 0 iterations".to_string()));
+        }
+
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let key = Self::derive_key(passphrase, &salt, iterations);
+
+        let mut encrypted = salt.to_vec();
+        encrypted.extend(Self::encrypt_bytes(plaintext, &key, compress)?);
+
+        Ok(general_purpose::STANDARD.encode(encrypted))
+    }
+
+    /// Encrypt vault contents with the key or passphrase of a dotenv key uri, honoring its
+    /// `compress` and `kdf` parameters
+    ///
+    /// This picks [`Vault::encrypt`] or [`Vault::encrypt_with_passphrase`] as needed, so the
+    /// ciphertext can be decrypted with the same dotenv key.
+    ///
+    /// # Examples
+    /// ```
+    /// use dotenv_vault::Vault;
+    ///
+    /// let key = "dotenv://:correct%20horse@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1000";
+    /// let ciphertext = Vault::encrypt_for_key(b"ALPHA=\"zeta\"", key).unwrap();
+    /// let vault = Vault::from_bytes(format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext), key);
+    /// assert_eq!(vault.get("ALPHA").unwrap().unwrap(), "zeta");
+    /// ```
+    pub fn encrypt_for_key(plaintext: &[u8], dotenv_key: &str) -> Result<String> {
+        let raw_key = Self::url_key(&url::Url::parse(dotenv_key.trim())?)?;
+        Self::encrypt_with_options(plaintext, &raw_key, Self::key_options(dotenv_key)?)
+    }
+
+    /// Encrypt vault contents with the key or passphrase of a dotenv key, as described by its
+    /// options
+    fn encrypt_with_options(plaintext: &[u8], key: &str, options: KeyOptions) -> Result<String> {
+        match options.pbkdf2_iterations {
+            Some(iterations) => {
+                // The passphrase is percent-encoded in the dotenv key uri
+                let passphrase: Vec<u8> = percent_encoding::percent_decode_str(key).collect();
+                Self::encrypt_with_passphrase_bytes(
+                    plaintext,
+                    &passphrase,
+                    iterations,
+                    options.compressed,
+                )
+            }
+            None => Self::encrypt(plaintext, key, options.compressed),
+        }
+    }
+
+    /// Encrypt the variables in `map` into a *.env.vault* file entry for the environment of a
+    /// dotenv key, e.g. to migrate a plaintext *.env* file
+    ///
+    /// The variables are written in dotenv format, sorted by name, and encrypted like
+    /// [`Vault::encrypt`], honoring the `compress` and `kdf` parameters of the key.
+    ///
+    /// # Arguments
+    /// - `map` - The variables to encrypt
+    /// - `key` - The dotenv key uri, whose first `environment` parameter names the entry
+    ///
+    /// # Returns
+    /// A `Result` containing a line such as `DOTENV_VAULT_PRODUCTION="ciphertext"`, ready to be
+    /// appended to a *.env.vault* file
+    ///
+    /// # Examples
+    /// ```
+    /// use dotenv_vault::Vault;
+    /// use std::collections::HashMap;
+    ///
+    /// let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+    /// let map = HashMap::from([("ALPHA".to_string(), "zeta".to_string())]);
+    ///
+    /// let entry = Vault::import_from_hashmap(&map, key).unwrap();
+    /// assert!(entry.starts_with("DOTENV_VAULT_PRODUCTION=\""));
+    /// assert_eq!(Vault::from_bytes(entry, key).get("ALPHA").unwrap().unwrap(), "zeta");
+    /// ```
+    pub fn import_from_hashmap(map: &HashMap<String, String>, key: &str) -> Result<String> {
+        let url = url::Url::parse(key.trim())?;
+        let raw_key = Self::url_key(&url)?;
+        let environment = match url.query_pairs().find(|(k, _)| k == "environment") {
+            Some((_, environment)) => environment.into_owned(),
+            None => return Err(Error::MissingEnvironment),
+        };
+        let options = Self::key_options(key)?;
+
+        let mut variables: Vec<(&String, &String)> = map.iter().collect();
+        variables.sort();
+        let plaintext: String = variables
+            .into_iter()
+            .map(|(name, value)| format!("{}=\"{}\"\n", name, escape(value)))
+            .collect();
+
+        let ciphertext = Self::encrypt_with_options(plaintext.as_bytes(), &raw_key, options)?;
+
+        Ok(format!(
+            "{}{}=\"{}\"",
+            VaultConfig::default().vault_key_prefix,
+            environment.to_uppercase(),
+            ciphertext
+        ))
+    }
+
+    /// Generate a new random dotenv key for an environment
+    ///
+    /// The key is 32 random bytes from the operating system, and the host of the dotenv key uri
+    /// is a random UUID so keys of different projects can be told apart.
+    ///
+    /// # Returns
+    /// A tuple of `(key_uri, hex_key)`, where `key_uri` is the dotenv key uri to set as
+    /// *DOTENV_KEY* and `hex_key` the 64 hex characters to pass to [`Vault::encrypt`]
+    ///
+    /// # Examples
+    /// ```
+    /// use dotenv_vault::Vault;
+    ///
+    /// let (key_uri, hex_key) = Vault::generate_key("production");
+    /// let ciphertext = Vault::encrypt(b"ALPHA=\"zeta\"", &hex_key, false).unwrap();
+    /// let vault = Vault::from_bytes(
+    ///     format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext),
+    ///     &key_uri,
+    /// );
+    /// assert_eq!(vault.get("ALPHA").unwrap().unwrap(), "zeta");
+    /// ```
+    pub fn generate_key(environment: &str) -> (String, String) {
+        use aes_gcm::{
+            aead::{rand_core::RngCore, KeyInit, OsRng},
+            Aes256Gcm,
+        };
+
+        let hex_key = hex::encode(Aes256Gcm::generate_key(OsRng));
+
+        // A version 4 UUID, as described in RFC 4122
+        let mut uuid = [0; 16];
+        OsRng.fill_bytes(&mut uuid);
+        uuid[6] = (uuid[6] & 0x0f) | 0x40;
+        uuid[8] = (uuid[8] & 0x3f) | 0x80;
+        let uuid = hex::encode(uuid);
+
+        let key_uri = format!(
+            "dotenv://:key_{}@{}-{}-{}-{}-{}/vault/.env.vault?environment={}",
+            hex_key,
+            &uuid[..8],
+            &uuid[8..12],
+            &uuid[12..16],
+            &uuid[16..20],
+            &uuid[20..],
+            percent_encoding::utf8_percent_encode(environment, percent_encoding::NON_ALPHANUMERIC),
+        );
+
+        (key_uri, hex_key)
+    }
+
+    /// Encrypt vault contents with AES-256-GCM, prefixed with a random nonce
+    fn encrypt_bytes(plaintext: &[u8], key: &[u8], compress: bool) -> Result<Vec<u8>> {
+        use aes_gcm::{
+            aead::{Aead, AeadCore, KeyInit, OsRng},
+            Aes256Gcm, Key,
+        };
+
+        let key = Key::<Aes256Gcm>::from_slice(key);
+        let cipher = Aes256Gcm::new(key);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+        let compressed;
+        let plaintext = if compress {
+            compressed = Self::compress(plaintext)?;
+            &compressed[..]
+        } else {
+            plaintext
+        };
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend(cipher.encrypt(&nonce, plaintext)?);
+
+        Ok(encrypted)
+    }
+
+    /// Parse the dotenv key uri into a key and environment
+    ///
+    /// # Arguments
+    /// - `dotenv_key` - The dotenv key uri
+    ///
+    /// # Returns
+    /// A `Result` containing a tuple of `(key, environment)`, where `environment` is the name of
+    /// the matching entry in the *.env.vault* file, e.g. `DOTENV_VAULT_PRODUCTION`, or
+    /// `DOTENV_VAULT_PRODUCTION_APP` with the [`VaultConfig::vault_key_suffix`] `_APP`
+    pub fn instructions(&self, dotenv_key: &str) -> Result<(String, String)> {
+        let (key, mut environment_keys) = self.instructions_multi(dotenv_key)?;
+        Ok((key, environment_keys.swap_remove(0)))
+    }
+
+    /// Parse the dotenv key uri into a key and every environment it lists, for keys such as
+    /// `dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&environment=staging`
+    ///
+    /// # Arguments
+    /// - `dotenv_key` - The dotenv key uri
+    ///
+    /// # Returns
+    /// A `Result` containing a tuple of `(key, environments)`, where `environments` holds the
+    /// names of the matching entries in the *.env.vault* file, in the order they are listed.
+    /// Other parameters, such as the `key_id` read by [`Vault::key_id`], are ignored.
+    pub fn instructions_multi(&self, dotenv_key: &str) -> Result<(String, Vec<String>)> {
+        // The url parser silently drops embedded tabs and newlines, which are always a mistake
+        let dotenv_key = dotenv_key.trim();
+        if dotenv_key.contains(|c: char| c.is_ascii_whitespace()) {
+            return Err(Error::InvalidKey);
+        }
+
+        let url = url::Url::parse(dotenv_key)?;
+        let key = Self::url_key(&url)?;
+
+        if let Some(environment) = self.target_environment.as_ref() {
+            return Ok((key, vec![self.entry_name(environment)]));
+        }
+
+        let environment_keys: Vec<String> = url
+            .query_pairs()
+            .filter(|(k, _)| k == "environment")
+            .map(|(_, environment)| self.entry_name(&environment))
+            .collect();
+
+        if environment_keys.is_empty() {
+            return Err(Error::MissingEnvironment);
+        }
+
+        Ok((key, environment_keys))
+    }
+
+    /// The raw key of a dotenv key, which is either a dotenv key uri or only the raw hex key
+    fn raw_key(dotenv_key: &str) -> Result<String> {
+        let dotenv_key = dotenv_key.trim();
+        if dotenv_key.contains(|c: char| c.is_ascii_whitespace()) {
+            return Err(Error::InvalidKey);
+        }
+
+        if !dotenv_key.contains("://") {
+            return Ok(dotenv_key.to_string());
+        }
+
+        Self::url_key(&url::Url::parse(dotenv_key)?)
+    }
+
+    /// The raw key in the password of a dotenv key uri
+    fn url_key(url: &url::Url) -> Result<String> {
+        if url.scheme() != "dotenv" {
+            return Err(Error::InvalidScheme);
+        }
+
+        let key = match url.password() {
+            Some(key) => key.to_string(),
+            None => return Err(Error::MissingKey),
+        };
+
+        Self::key_format_version(url)?;
+        Ok(key)
+    }
+
+    /// Name of the *.env.vault* file entry of an environment, e.g. `DOTENV_VAULT_PRODUCTION` for
+    /// `production`
+    fn entry_name(&self, environment: &str) -> String {
+        format!(
+            "{}{}{}",
+            self.config.vault_key_prefix,
+            environment.to_uppercase(),
+            self.config.vault_key_suffix.as_deref().unwrap_or_default()
+        )
+    }
+
+    /// Lowercase environment of a *.env.vault* file entry, the inverse of [`Vault::entry_name`],
+    /// or `None` if the entry does not have the configured prefix and suffix
+    fn entry_environment(&self, entry: &str) -> Option<String> {
+        let environment = entry.strip_prefix(self.config.vault_key_prefix.as_str())?;
+        let environment = match self.config.vault_key_suffix.as_deref() {
+            Some(suffix) => environment.strip_suffix(suffix)?,
+            None => environment,
+        };
+
+        Some(environment.to_lowercase())
+    }
+
+    /// Split a comma-separated dotenv key into its keys, ordered by `config.key_order`
+    fn ordered_keys<'a>(&self, keys: &'a str) -> Vec<&'a str> {
+        let mut keys: Vec<&str> = keys.split(',').collect();
+
+        match self.config.key_order {
+            KeyOrder::DocumentOrder => {}
+            KeyOrder::ReverseDocumentOrder => keys.reverse(),
+            KeyOrder::MostRecentFirst => {
+                keys.sort_by_key(|key| std::cmp::Reverse(Self::key_timestamp(key)))
+            }
+        }
+
+        keys
+    }
+
+    /// The timestamp in the `timestamp` parameter of a dotenv key uri, if any
+    fn key_timestamp(key: &str) -> Option<u64> {
+        let url = url::Url::parse(key.trim()).ok()?;
+        let (_, timestamp) = url.query_pairs().find(|(k, _)| k == "timestamp")?;
+        timestamp.parse().ok()
+    }
+
+    /// Detect the *.env.vault* format version from the `version` parameter of the dotenv key,
+    /// such as `dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&version=1`,
+    /// defaulting to `1` when it is absent
+    ///
+    /// # Returns
+    /// A `Result` containing the format version, or [`Error::UnsupportedVaultVersion`] if any of
+    /// the comma-separated keys has a version other than `1`. A version that is not a number
+    /// from `0` to `255`, such as `version=256` or `version=abc`, is reported as
+    /// [`Error::InvalidKey`] instead.
+    pub fn format_version(&self) -> Result<u8> {
+        let keys = match self.key.as_ref() {
+            Some(key) => key,
+            None => return Err(Error::KeyNotFound),
+        };
+
+        for key in keys.split(',') {
+            Self::key_format_version(&url::Url::parse(key.trim())?)?;
+        }
+
+        Ok(1)
+    }
+
+    /// Read and validate the format version of a dotenv key uri, see [`Vault::format_version`]
+    fn key_format_version(url: &url::Url) -> Result<u8> {
+        let version = match url.query_pairs().find(|(k, _)| k == "version") {
+            Some((_, version)) => version.parse().map_err(|_| Error::InvalidKey)?,
+            None => 1,
+        };
+
+        match version {
+            1 => Ok(version),
+            _ => Err(Error::UnsupportedVaultVersion(version)),
+        }
+    }
+
+    /// Whether the vault entries of the dotenv key are compressed, as set by the `compress`
+    /// parameter, such as
+    /// `dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&compress=zstd`
+    ///
+    /// # Returns
+    /// A `Result` containing whether the entries are zstd compressed, or
+    /// [`Error::UnsupportedCompression`] for other compressions and for zstd without the
+    /// `compress` feature
+    pub fn uses_compression(dotenv_key: &str) -> Result<bool> {
+        let url = url::Url::parse(dotenv_key.trim())?;
+        let compression = match url.query_pairs().find(|(k, _)| k == "compress") {
+            Some((_, compression)) => compression,
+            None => return Ok(false),
+        };
+
+        match compression.as_ref() {
+            #[cfg(all(feature = "compress", not(target_arch = "wasm32")))]
+            "zstd" => Ok(true),
+            _ => Err(Error::UnsupportedCompression(compression.into_owned())),
+        }
+    }
+
+    /// Read how the entries of a dotenv key are encrypted from its `compress`, `kdf` and
+    /// `iterations` parameters
+    fn key_options(dotenv_key: &str) -> Result<KeyOptions> {
+        let url = url::Url::parse(dotenv_key.trim())?;
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        let pbkdf2_iterations = match param("kdf").as_deref() {
+            None => None,
+            Some("pbkdf2") => match param("iterations") {
+                None => Some(DEFAULT_PBKDF2_ITERATIONS),
+                Some(iterations) => match iterations.parse() {
+                    Ok(iterations) if iterations > 0 => Some(iterations),
+                    _ => {
+                        return Err(Error::InvalidKdf(format!(
+                            "pbkdf2 with {} iterations",
+                            iterations
+                        )))
+                    }
+                },
+            },
+            Some(kdf) => return Err(Error::InvalidKdf(kdf.to_string())),
+        };
+
+        Ok(KeyOptions {
+            compressed: Self::uses_compression(dotenv_key)?,
+            pbkdf2_iterations,
+        })
+    }
+
+    /// Compress vault contents before encrypting them
+    fn compress(plaintext: &[u8]) -> Result<Vec<u8>> {
+        #[cfg(all(feature = "compress", not(target_arch = "wasm32")))]
+        return Ok(zstd::stream::encode_all(plaintext, 0)?);
+
+        #[cfg(not(all(feature = "compress", not(target_arch = "wasm32"))))]
+        {
+            let _ = plaintext;
+            Err(Error::UnsupportedCompression("zstd".to_string()))
+        }
+    }
+
+    /// Decompress decrypted vault contents if they are compressed
+    fn decompress(decrypted: Vec<u8>, compressed: bool) -> Result<Vec<u8>> {
+        if !compressed {
+            return Ok(decrypted);
+        }
+
+        #[cfg(all(feature = "compress", not(target_arch = "wasm32")))]
+        return Ok(zstd::stream::decode_all(&decrypted[..])?);
+
+        #[cfg(not(all(feature = "compress", not(target_arch = "wasm32"))))]
+        Err(Error::UnsupportedCompression("zstd".to_string()))
+    }
+
+    /// Read the entries of the *.env.vault* file, preferring the in-memory contents over the path
+    fn entries(&self) -> Result<Vec<(String, String)>> {
+        if let Some(contents) = self.contents.as_ref() {
+            return Ok(Self::normalize_entries(dotenvy::from_read_iter(
+                &contents[..],
+            )));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = self.vault_path() {
+            return Ok(Self::normalize_entries(dotenvy::from_path_iter(path)?));
+        }
+
+        Err(Error::VaultNotFound)
+    }
+
+    /// Skip unparsable entries and strip the carriage returns that CRLF line endings can leave in
+    /// ciphertexts
+    fn normalize_entries(
+        entries: impl Iterator<Item = dotenvy::Result<(String, String)>>,
+    ) -> Vec<(String, String)> {
+        entries
+            .filter_map(|item| item.ok())
+            .map(|(name, ciphertext)| (name, ciphertext.replace('\r', "")))
+            .collect()
+    }
+
+    /// Parse the in-memory contents given to [`Vault::from_bytes`] into a `Vec<u8>`
+    fn parse_memory(&self) -> Result<Vec<u8>> {
+        if self.contents.is_none() {
+            return Err(Error::VaultNotFound);
+        }
+
+        self.parse()
+    }
+
+    /// Parse the *.env.vault* file into a `Vec<u8>`
+    ///
+    /// # Returns
+    /// A `Result` containing a `Vec<u8>` of the decrypted vault contents
+    fn parse(&self) -> Result<Vec<u8>> {
+        if self.config.detect_nonce_reuse {
+            self.validate_nonce_uniqueness(&mut HashSet::new())?;
+        }
+
+        let (_, decrypted) = self.decrypt_entry()?;
+        self.normalize(decrypted)
+    }
+
+    /// Apply [`VaultConfig::interpolate`] and [`VaultConfig::strip_outer_quotes`] to decrypted
+    /// contents
+    fn normalize(&self, decrypted: Vec<u8>) -> Result<Vec<u8>> {
+        let decrypted = self.interpolated(decrypted)?;
+        self.quotes_stripped(decrypted)
+    }
+
+    /// Expand variable references in decrypted contents if [`VaultConfig::interpolate`] is set
+    fn interpolated(&self, decrypted: Vec<u8>) -> Result<Vec<u8>> {
+        if !self.config.interpolate {
+            return Ok(decrypted);
+        }
+
+        let contents = String::from_utf8(decrypted)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        Ok(interpolate(&contents)?.into_bytes())
+    }
+
+    /// Strip one layer of matching quotes from each value if [`VaultConfig::strip_outer_quotes`]
+    /// is set, writing the variables back in the same order
+    fn quotes_stripped(&self, decrypted: Vec<u8>) -> Result<Vec<u8>> {
+        if !self.config.strip_outer_quotes {
+            return Ok(decrypted);
+        }
+
+        let mut contents = String::with_capacity(decrypted.len());
+        for variable in dotenvy::from_read_iter(&decrypted[..]) {
+            let (key, value) = variable?;
+            contents.push_str(&format!(
+                "{}=\"{}\"\n",
+                key,
+                escape(strip_outer_quotes(&value))
+            ));
+        }
+
+        Ok(contents.into_bytes())
+    }
+
+    /// Check that no two entries of the *.env.vault* file are encrypted with the same nonce, which
+    /// breaks the security of AES-GCM
+    ///
+    /// The nonce of entries named by a dotenv key with `kdf=pbkdf2` is read after their salt.
+    ///
+    /// # Arguments
+    /// - `seen_nonces` - Nonces already seen, such as those of another *.env.vault* file
+    ///   encrypted with the same key; the nonces of this vault are added to it
+    ///
+    /// # Returns
+    /// A `Result` that is [`Error::NonceReuse`] if a nonce is in `seen_nonces` or used twice
+    pub fn validate_nonce_uniqueness(&self, seen_nonces: &mut HashSet<[u8; 12]>) -> Result<()> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let salted_entries = self.salted_entries();
+        for (name, encrypted) in self.entries()? {
+            if self.entry_environment(&name).is_none() {
+                continue;
+            }
+
+            let ciphertext = general_purpose::STANDARD.decode(encrypted)?;
+            let offset = if salted_entries.contains(&name) {
+                SALT_LEN
+            } else {
+                0
+            };
+            if ciphertext.len() < offset + 12 {
+                return Err(Error::InvalidCiphertext {
+                    actual_len: ciphertext.len(),
+                    min_len: offset + 12 + 16,
+                });
+            }
+
+            let mut nonce = [0; 12];
+            nonce.copy_from_slice(&ciphertext[offset..offset + 12]);
+
+            if !seen_nonces.insert(nonce) {
+                return Err(Error::NonceReuse);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Names of the *.env.vault* file entries of the dotenv keys with `kdf=pbkdf2`, whose
+    /// ciphertext starts with a salt
+    fn salted_entries(&self) -> HashSet<String> {
+        let keys = match self.key.as_ref() {
+            Some(keys) => keys,
+            None => return HashSet::new(),
+        };
+
+        keys.split(',')
+            .filter(|key| {
+                Self::key_options(key).is_ok_and(|options| options.pbkdf2_iterations.is_some())
+            })
+            .filter_map(|key| self.instructions_multi(key).ok())
+            .flat_map(|(_, entries)| entries)
+            .collect()
+    }
+
+    /// Decrypt the first `(key, entry, ciphertext, options)` candidate that can be decrypted,
+    /// trying them one after another
+    #[cfg(not(feature = "parallel"))]
+    fn decrypt_first(&self, candidates: Vec<Candidate>) -> Option<Candidate<Vec<u8>>> {
+        candidates
+            .into_iter()
+            .find_map(|(key, entry, ciphertext, options)| {
+                let decrypted = self.decrypt_with_options(ciphertext, &key, options).ok()?;
+                Some((key, entry, decrypted, options))
+            })
+    }
+
+    /// Decrypt the first `(key, entry, ciphertext, options)` candidate that can be decrypted,
+    /// trying them concurrently
+    #[cfg(feature = "parallel")]
+    fn decrypt_first(&self, candidates: Vec<Candidate>) -> Option<Candidate<Vec<u8>>> {
+        use rayon::prelude::*;
+
+        candidates
+            .into_par_iter()
+            .find_map_first(|(key, entry, ciphertext, options)| {
+                let decrypted = self.decrypt_with_options(ciphertext, &key, options).ok()?;
+                Some((key, entry, decrypted, options))
+            })
+    }
+
+    /// Decrypt the *.env.vault* file entry for the dotenv key, without falling back to a regular
+    /// *.env* file or modifying the environment
+    ///
+    /// # Returns
+    /// A `Result` containing a tuple of `(entry, decrypted)`, where `entry` is the name of the
+    /// vault entry that was decrypted and `decrypted` its plaintext contents
+    pub fn decrypt_entry(&self) -> Result<(String, Vec<u8>)> {
+        let keys = match self.key.as_ref() {
+            Some(key) => key,
+            None => return Err(Error::KeyNotFound),
+        };
+
+        let entries = self.entries()?;
+
+        let mut candidates = Vec::new();
+        let mut key_ids = HashMap::new();
+        let mut requested = None;
+        let mut options_error = None;
+        for key in self.ordered_keys(keys) {
+            let (k, environments) = match self.instructions_multi(key) {
+                Ok(instructions) => instructions,
+                Err(_) => continue,
+            };
+            // A key with unsupported options, e.g. a stale one during rotation, is skipped
+            let options = match Self::key_options(key) {
+                Ok(options) => options,
+                Err(err) => {
+                    options_error.get_or_insert(err);
+                    continue;
+                }
+            };
+            // The key id is only audit metadata, so it never fails decryption
+            key_ids
+                .entry(k.clone())
+                .or_insert_with(|| Self::key_id(key).ok().flatten());
+
+            if requested.is_none() {
+                requested = environments.first().cloned();
+            }
+
+            for e in environments {
+                if let Some((_, c)) = entries.iter().find(|(name, _)| name == &e) {
+                    candidates.push((k.clone(), e, c.clone(), options));
+                }
+            }
+        }
+
+        let candidates_empty = candidates.is_empty();
+        if let (true, Some(err)) = (candidates_empty, options_error) {
+            return Err(err);
+        }
+        if let Some((k, e, decrypted, options)) = self.decrypt_first(candidates) {
+            self.record_key(&k, key_ids.remove(&k).flatten());
+            return Ok((e, Self::decompress(decrypted, options.compressed)?));
+        }
+
+        match requested {
+            Some(requested) if candidates_empty => Err(Error::EnvironmentNotFound {
+                requested,
+                available: self.available_environments(&entries),
+            }),
+            _ => Err(Error::InvalidKey),
+        }
+    }
+
+    /// Names of the environments in the *.env.vault* file, e.g. `production` for the
+    /// `DOTENV_VAULT_PRODUCTION` entry
+    fn available_environments(&self, entries: &[(String, String)]) -> Vec<String> {
+        entries
+            .iter()
+            .filter_map(|(name, _)| self.entry_environment(name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+    use std::{fs::File, io::prelude::*};
+
+    use super::*;
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn new_ok() {
+        std::env::set_var("DOTENV_KEY", "dotenv://:testkey");
+        let vault = Vault::new();
+        assert!(vault.key.is_some());
+        assert!(vault.key.unwrap() == "dotenv://:testkey");
+        assert!(vault.path.is_some());
+        assert!(vault.path.unwrap() == env::current_dir().unwrap().join(".env.vault"));
+        std::env::remove_var("DOTENV_KEY");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn new_trims_key() {
+        let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+        for padded in [
+            format!("  {}  ", key),
+            format!("{}\n", key),
+            format!("{}\r\n", key),
+            format!("\t{}\t", key),
+        ] {
+            env::set_var("DOTENV_KEY", &padded);
+            let vault = Vault::new();
+            assert_eq!(vault.key.as_deref(), Some(key));
+            assert!(vault.instructions(&padded).is_ok());
+        }
+
+        env::remove_var("DOTENV_KEY");
+    }
+
+    #[test]
+    fn instructions_embedded_space() {
+        let vault = Vault::new();
+        let instructions = vault.instructions(
+            "dotenv://:key_ddcaa26504cd70a6fef98019 01c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        assert!(matches!(instructions, Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    fn instructions_embedded_tab() {
+        let vault = Vault::new();
+        let instructions = vault.instructions(
+            "dotenv://:key_ddcaa26504cd70a6fef98019\t01c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        assert!(matches!(instructions, Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    #[serial] // Run serially due to cwd modifications
+    fn from_key_ok() {
+        let vault = Vault::from_key(" dotenv://:testkey\n");
+        assert_eq!(vault.key.unwrap(), "dotenv://:testkey");
+        assert_eq!(
+            vault.path.unwrap(),
+            env::current_dir().unwrap().join(".env.vault")
+        );
+    }
+
+    #[test]
+    fn instructions_ok() {
+        let vault = Vault::new();
+        let instructions = vault
+            .instructions("dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production");
+
+        assert!(instructions.is_ok());
+        let (key, environment) = instructions.unwrap();
+        assert_eq!(key, "key_1234");
+        assert_eq!(environment, "DOTENV_VAULT_PRODUCTION");
+    }
+
+    #[test]
+    fn instructions_unsupported_version() {
+        let vault = Vault::new();
+        let instructions = vault.instructions(
+            "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&version=2",
+        );
+
+        assert!(matches!(
+            instructions,
+            Err(Error::UnsupportedVaultVersion(2))
+        ));
+    }
+
+    #[test]
+    fn format_version_default() {
+        let vault = Vault::from_key(
+            "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production",
+        );
+
+        assert_eq!(vault.format_version().unwrap(), 1);
+    }
+
+    #[test]
+    fn format_version_ok() {
+        let vault = Vault::from_key(
+            "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&version=1",
+        );
+
+        assert_eq!(vault.format_version().unwrap(), 1);
+    }
+
+    #[test]
+    fn format_version_unsupported() {
+        let vault = Vault::from_key("dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&version=1,dotenv://:key_5678@dotenv.org/vault/.env.vault?environment=production&version=2");
+
+        assert!(matches!(
+            vault.format_version(),
+            Err(Error::UnsupportedVaultVersion(2))
+        ));
+
+        for version in ["256", "abc"] {
+            let vault = Vault::from_key(&format!(
+                "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&version={}",
+                version
+            ));
+            assert!(matches!(vault.format_version(), Err(Error::InvalidKey)));
+        }
+    }
+
+    #[test]
+    fn instructions_multi_ok() {
+        let vault = Vault::new();
+        let instructions = vault.instructions_multi(
+            "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&environment=staging",
+        );
+
+        assert!(instructions.is_ok());
+        let (key, environments) = instructions.unwrap();
+        assert_eq!(key, "key_1234");
+        assert_eq!(
+            environments,
+            vec!["DOTENV_VAULT_PRODUCTION", "DOTENV_VAULT_STAGING"]
+        );
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn new_for_environment_matching() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let vault = Vault {
+            contents: Some(
+                "DOTENV_VAULT_DEVELOPMENT=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"\nDOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"".into(),
+            ),
+            ..Vault::new_for_environment("production")
+        };
+
+        env::remove_var("DOTENV_KEY");
+
+        assert_eq!(vault.decrypt_entry().unwrap().0, "DOTENV_VAULT_PRODUCTION");
+        assert_eq!(vault.to_map().unwrap()["ALPHA"], "omega");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn new_for_environment_conflicting() {
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let vault = Vault {
+            contents: Some(
+                "DOTENV_VAULT_DEVELOPMENT=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"\nDOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"".into(),
+            ),
+            ..Vault::new_for_environment("development")
+        };
+
+        env::remove_var("DOTENV_KEY");
+
+        let (key, environment) = vault
+            .instructions("dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=production")
+            .unwrap();
+        assert_eq!(key, "key_1234");
+        assert_eq!(environment, "DOTENV_VAULT_DEVELOPMENT");
+        assert_eq!(vault.decrypt_entry().unwrap().0, "DOTENV_VAULT_DEVELOPMENT");
+        assert_eq!(vault.to_map().unwrap()["ALPHA"], "zeta");
+    }
+
+    #[test]
+    fn instructions_first_of_multi() {
+        let vault = Vault::new();
+        let instructions = vault.instructions(
+            "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&environment=staging",
+        );
+
+        assert!(instructions.is_ok());
+        let (key, environment) = instructions.unwrap();
+        assert_eq!(key, "key_1234");
+        assert_eq!(environment, "DOTENV_VAULT_PRODUCTION");
+    }
+
+    #[test]
+    fn vault_key_suffix_custom() {
+        let contents = "DOTENV_VAULT_PRODUCTION_APP=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"\nDOTENV_VAULT_STAGING=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"";
+        let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+        let vault = Vault::from_bytes(contents, key).with_vault_key_suffix("_APP");
+        let (_, environment) = vault.instructions(key).unwrap();
+        assert_eq!(environment, "DOTENV_VAULT_PRODUCTION_APP");
+        assert_eq!(vault.to_map().unwrap()["ALPHA"], "omega");
+        assert_eq!(vault.verify().unwrap(), ("production".to_string(), 2));
+
+        let environments = vault.load_all_environments().unwrap();
+        assert_eq!(environments.len(), 1);
+        assert_eq!(environments["production"]["BETA"], "gamma");
+
+        let vault = Vault::from_bytes(
+            contents,
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=staging",
+        )
+        .with_vault_key_suffix("_APP");
+        assert!(matches!(
+            vault.to_map(),
+            Err(Error::EnvironmentNotFound { requested, available })
+                if requested == "DOTENV_VAULT_STAGING_APP" && available == ["production"]
+        ));
+    }
+
+    #[test]
+    fn vault_key_prefix_custom() {
+        let contents = "MYAPP_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"";
+        let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+        let vault = Vault::from_bytes(contents, key).with_vault_key_prefix("MYAPP_VAULT_");
+        let (_, environment) = vault.instructions(key).unwrap();
+        assert_eq!(environment, "MYAPP_VAULT_PRODUCTION");
+        assert_eq!(vault.to_map().unwrap()["ALPHA"], "omega");
+        assert_eq!(
+            vault.load_all_environments().unwrap()["production"]["BETA"],
+            "gamma"
+        );
+
+        let vault = Vault::from_bytes(contents, key);
+        assert!(matches!(
+            vault.to_map(),
+            Err(Error::EnvironmentNotFound { requested, available })
+                if requested == "DOTENV_VAULT_PRODUCTION" && available.is_empty()
+        ));
+    }
+
+    #[test]
+    fn instructions_multi_missing_environment() {
+        let vault = Vault::new();
+        let instructions =
+            vault.instructions_multi("dotenv://:key_1234@dotenv.org/vault/.env.vault");
+
+        assert!(matches!(
+            instructions.unwrap_err(),
+            Error::MissingEnvironment
+        ));
+    }
+
+    #[test]
+    fn instructions_invalid_scheme() {
+        let vault = Vault::new();
+        let instructions =
+            vault.instructions("invalid://dotenv.org/vault/.env.vault?environment=production");
+
+        assert!(instructions.is_err());
+        assert!(matches!(instructions.unwrap_err(), Error::InvalidScheme));
+    }
+
+    #[test]
+    fn instructions_missing_key() {
+        let vault = Vault::new();
+        let instructions =
+            vault.instructions("dotenv://dotenv.org/vault/.env.vault?environment=production");
+
+        assert!(instructions.is_err());
+        assert!(matches!(instructions.unwrap_err(), Error::MissingKey));
+    }
+
+    #[test]
+    fn instructions_missing_environment() {
+        let vault = Vault::new();
+        let instructions = vault.instructions("dotenv://:key_1234@dotenv.org/vault/.env.vault");
+
+        assert!(instructions.is_err());
+        assert!(matches!(
+            instructions.unwrap_err(),
+            Error::MissingEnvironment
+        ));
+    }
+
+    #[test]
+    fn decrypt_ok() {
+        let vault = Vault::new();
+        let decrypted = vault.decrypt(
+            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R".into(),
+            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
+        );
+        assert!(decrypted.is_ok());
+        assert_eq!(
+            decrypted.unwrap(),
+            "# development@v6\nALPHA=\"zeta\"".as_bytes()
+        );
+    }
+
+    #[test]
+    fn decrypt_invalid_key() {
+        let vault = Vault::new();
+        let decrypted = vault.decrypt(
+            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R".into(),
+            "01b08fe1173b781cce5fd1a18178c5cacdf3bb0845a8aa1b8089ac0751f7ed9c".into(),
+        );
+        assert!(matches!(decrypted, Err(Error::DecryptError(_))));
+    }
+
+    #[test]
+    fn decrypt_invalid_ciphertext() {
+        let vault = Vault::new();
+        let decrypted = vault.decrypt(
+            "bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==".into(),
+            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
+        );
+        assert!(matches!(decrypted, Err(Error::DecryptError(_))));
+    }
+
+    #[test]
+    fn decrypt_short_ciphertext() {
+        let vault = Vault::new();
+        let decrypted = vault.decrypt(
+            "s7NYXa809k/bVSPwIAmJhPJmEGTt".into(),
+            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
+        );
+        assert!(matches!(
+            decrypted,
+            Err(Error::InvalidCiphertext {
+                actual_len: 21,
+                min_len: 28
+            })
+        ));
+    }
+
+    #[test]
+    fn decrypt_short_key() {
+        let vault = Vault::new();
+        let decrypted = vault.decrypt(
+            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R".into(),
+            "caa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
+        );
+        assert!(matches!(decrypted, Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    fn decrypt_invalid_hex() {
+        let vault = Vault::new();
+        let decrypted = vault.decrypt(
+            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R".into(),
+            "XXcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
+        );
+        assert!(matches!(decrypted, Err(Error::HexError(_))));
+    }
+
+    #[test]
+    fn decrypt_invalid_base64() {
+        let vault = Vault::new();
+        let decrypted = vault.decrypt(
+            "FFFFFFFs7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R"
+                .into(),
+            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
+        );
+        assert!(matches!(decrypted, Err(Error::DecodeError(_))));
+    }
+
+    #[test]
+    fn encrypt_round_trip() {
+        let vault = Vault::new();
+        let plaintext = "ALPHA=\"zeta\"".as_bytes();
+        let key = "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00";
+
+        let encrypted = Vault::encrypt(plaintext, key, false);
+        assert!(encrypted.is_ok());
+
+        let decrypted = vault.decrypt(encrypted.unwrap(), key.into());
+        assert!(decrypted.is_ok());
+        assert_eq!(decrypted.unwrap(), plaintext);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn encrypt_decrypt_property(
+            plaintext in proptest::collection::vec(proptest::num::u8::ANY, 0..512),
+            key in "[0-9a-f]{64}",
+        ) {
+            let vault = Vault::new();
+            let encrypted = Vault::encrypt(&plaintext, &key, false).unwrap();
+            let decrypted = vault.decrypt(encrypted, key).unwrap();
+            proptest::prop_assert_eq!(decrypted, plaintext);
+        }
+    }
+
+    #[test]
+    fn encrypt_short_key() {
+        let encrypted = Vault::encrypt(
+            "ALPHA=\"zeta\"".as_bytes(),
+            "caa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00",
+            false,
+        );
+        assert!(matches!(encrypted, Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    fn decrypt_entry_unsupported_compression() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production&compress=gzip",
+        );
+
+        assert!(matches!(
+            vault.decrypt_entry(),
+            Err(Error::UnsupportedCompression(ref compression)) if compression == "gzip"
+        ));
+
+        // Other keys are still tried
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production&compress=gzip,dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        assert_eq!(vault.to_map().unwrap()["ALPHA"], "zeta");
+    }
+
+    #[test]
+    fn passphrase_round_trip() {
+        let ciphertext =
+            Vault::encrypt_with_passphrase(b"ALPHA=\"zeta\"", "correct horse", 1000, false)
+                .unwrap();
+        let contents = format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext);
+
+        let vault = Vault::from_bytes(
+            contents.clone(),
+            "dotenv://:correct%20horse@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1000",
+        );
+        let (entry, decrypted) = vault.decrypt_entry().unwrap();
+        assert_eq!(entry, "DOTENV_VAULT_PRODUCTION");
+        assert_eq!(decrypted, b"ALPHA=\"zeta\"");
+
+        let vault = Vault::from_bytes(
+            contents.clone(),
+            "dotenv://:wrong%20horse@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1000",
+        );
+        assert!(matches!(vault.decrypt_entry(), Err(Error::InvalidKey)));
+
+        let vault = Vault::from_bytes(
+            contents,
+            "dotenv://:correct%20horse@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1001",
+        );
+        assert!(matches!(vault.decrypt_entry(), Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    fn passphrase_non_utf8_round_trip() {
+        let key = "dotenv://:caf%FF@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1000";
+
+        let ciphertext = Vault::encrypt_for_key(b"ALPHA=\"zeta\"", key).unwrap();
+        let vault = Vault::from_bytes(format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext), key);
+        assert_eq!(vault.get("ALPHA").unwrap().unwrap(), "zeta");
+    }
+
+    #[test]
+    fn passphrase_missing_salt() {
+        let vault = Vault::new();
+        let options = KeyOptions {
+            compressed: false,
+            pbkdf2_iterations: Some(1000),
+        };
+
+        let decrypted = vault.decrypt_with_options("c2FsdA==".into(), "passphrase", options);
+        assert!(matches!(decrypted, Err(Error::MissingSalt)));
+    }
+
+    #[test]
+    fn key_options_kdf() {
+        let options = Vault::key_options(
+            "dotenv://:passphrase@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2",
+        );
+        assert_eq!(
+            options.unwrap(),
+            KeyOptions {
+                compressed: false,
+                pbkdf2_iterations: Some(DEFAULT_PBKDF2_ITERATIONS),
+            }
+        );
+
+        for query in [
+            "kdf=scrypt",
+            "kdf=pbkdf2&iterations=0",
+            "kdf=pbkdf2&iterations=many",
+        ] {
+            let vault = Vault::from_bytes(
+                "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"",
+                &format!(
+                    "dotenv://:passphrase@dotenv.local/vault/.env.vault?environment=production&{}",
+                    query
+                ),
+            );
+            assert!(matches!(vault.decrypt_entry(), Err(Error::InvalidKdf(_))));
+        }
+
+        assert!(matches!(
+            Vault::encrypt_with_passphrase(b"", "passphrase", 0, false),
+            Err(Error::InvalidKdf(_))
+        ));
+    }
+
+    #[test]
+    fn parse_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(&vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_DEVELOPMENT=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let vault = Vault {
+            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development".into()),
+            path: Some(vault_path),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_ok());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![
+                35, 32, 100, 101, 118, 101, 108, 111, 112, 109, 101, 110, 116, 64, 118, 54, 10, 65,
+                76, 80, 72, 65, 61, 34, 122, 101, 116, 97, 34
+            ]
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn parse_invalid_environment() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(&vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let vault = Vault {
+            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development".into()),
+            path: Some(vault_path),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_err());
+        assert!(matches!(
+            parsed.unwrap_err(),
+            Error::EnvironmentNotFound { requested, available }
+                if requested == "DOTENV_VAULT_DEVELOPMENT" && available == ["production"]
+        ));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn parse_invalid_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(&vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"XXNYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let vault = Vault {
+            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
+            path: Some(vault_path),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_err());
+        assert!(matches!(parsed.unwrap_err(), Error::InvalidKey));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn parse_multiple_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(&vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let vault = Vault {
+            key: Some("dotenv://:key_XXcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development,dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
+            path: Some(vault_path),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_ok());
+        assert_eq!(
+            parsed.unwrap(),
+            vec![
+                35, 32, 100, 101, 118, 101, 108, 111, 112, 109, 101, 110, 116, 64, 118, 54, 10, 65,
+                76, 80, 72, 65, 61, 34, 122, 101, 116, 97, 34
+            ]
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn decrypt_entry_many_keys() {
+        let contents = "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"";
+        let wrong_keys: Vec<String> = (1..5)
+            .map(|i| {
+                format!(
+                    "dotenv://:key_{}@dotenv.local/vault/.env.vault?environment=production",
+                    i.to_string().repeat(64)
+                )
+            })
+            .collect();
+        let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+        let vault = Vault::from_bytes(contents, &[wrong_keys.join(","), key.to_string()].join(","));
+        let (entry, decrypted) = vault.decrypt_entry().unwrap();
+        assert_eq!(entry, "DOTENV_VAULT_PRODUCTION");
+        assert_eq!(decrypted, "# development@v6\nALPHA=\"zeta\"".as_bytes());
+
+        let vault = Vault::from_bytes(contents, &wrong_keys.join(","));
+        assert!(matches!(vault.decrypt_entry(), Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    fn parse_crlf_line_endings() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        std::fs::write(
+            &vault_path,
+            b"#/-------------------.env.vault---------------------/\r\nDOTENV_VAULT_STAGING=\"bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==\"\r\nDOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"\r\n",
+        )
+        .unwrap();
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let parsed = vault.parse();
+
+        assert_eq!(
+            parsed.unwrap(),
+            "# development@v6\nALPHA=\"zeta\"".as_bytes()
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn to_map_unicode_name() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"zmUFq52BS0VjCPj+Fxl0OHm5dXzNrMt8BMuwjBoU1GqtP5IWcKrJsBi0ES5gTJDQ/Sh0A0xC2w==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        // dotenvy only accepts ASCII variable names, so there is nothing to normalize
+        assert!(matches!(
+            vault.to_map(),
+            Err(Error::DotenvyError(dotenvy::Error::LineParse(ref line, 3))) if line == "CAFÉ=\"value\""
+        ));
+    }
+
+    #[test]
+    fn parse_crlf_in_quotes() {
+        let vault = Vault::from_bytes(
+            b"DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\r\"\r\n".to_vec(),
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        assert!(vault.parse().is_ok());
+    }
+
+    #[test]
+    fn parse_crlf_unquoted() {
+        let vault = Vault::from_bytes(
+            b"DOTENV_VAULT_PRODUCTION=s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\r\n".to_vec(),
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        assert!(vault.parse().is_ok());
+    }
+
+    #[test]
+    fn parse_multiple_invalid_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(&vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let vault = Vault {
+            key: Some("dotenv://:key_XXcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development,dotenv://:key_XXYY6504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
+            path: Some(vault_path),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_err());
+        assert!(matches!(parsed.unwrap_err(), Error::InvalidKey));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_from_memory_ok() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        assert!(vault.path.is_none());
+
+        let result = vault.load_from_memory();
+        assert!(result.is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "zeta");
+
+        env::remove_var("ALPHA");
+    }
+
+    #[test]
+    fn load_from_memory_without_contents() {
+        let vault = Vault::from_key("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let result = vault.load_from_memory();
+        assert!(matches!(result, Err(Error::VaultNotFound)));
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn reload_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        let mut vault = File::create(&vault_path).unwrap();
+        vault
+            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+
+        let vault = Vault {
+            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
+            path: Some(vault_path.clone()),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+
+        assert!(vault.load().is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "zeta");
+        assert!(env::var("BETA").is_err());
+
+        let mut updated = File::create(&vault_path).unwrap();
+        updated
+            .write_all("DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"".as_bytes())
+            .unwrap();
+        updated.sync_all().unwrap();
+
+        assert!(vault.reload().is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "omega");
+        assert_eq!(env::var("BETA").unwrap(), "gamma");
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_additive_preserves_existing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        env::set_var("ALPHA", "beta");
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        assert!(vault.load_additive().is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "beta");
+        assert_eq!(env::var("BETA").unwrap(), "gamma");
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn apply_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let result = vault.apply(|key, value| {
+            (key == "ALPHA").then(|| ("ALPHA_UPPER".to_string(), value.to_uppercase()))
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(env::var("ALPHA_UPPER").unwrap(), "OMEGA");
+        assert!(env::var("ALPHA").is_err());
+        assert!(env::var("BETA").is_err());
+
+        env::remove_var("ALPHA_UPPER");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_strict_conflict() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        env::set_var("ALPHA", "beta");
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        assert_eq!(vault.load_check().unwrap(), vec!["ALPHA"]);
+
+        let result = vault.load_strict();
+        assert!(matches!(result, Err(Error::ConflictingVariables(ref keys)) if keys == &["ALPHA"]));
+        assert_eq!(env::var("ALPHA").unwrap(), "beta");
+        assert!(env::var("BETA").is_err());
+
+        env::remove_var("ALPHA");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_strict_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        env::set_var("ALPHA", "omega");
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        assert!(vault.load_check().unwrap().is_empty());
+        assert!(vault.load_strict().is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "omega");
+        assert_eq!(env::var("BETA").unwrap(), "gamma");
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn len_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(
+            &vault_path,
+            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R",
+        );
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        assert_eq!(vault.len().unwrap(), 1);
+        assert!(!vault.is_empty().unwrap());
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn get_typed_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "9gAS9K5KNxQG6o8Jx2m9OMPro7ieKf8S2W5Ww0LW0iYQfyr06FzEpeeobdOHR8wYY+OpP0LIdQRObLWyFOU3a9rmiSVVBD14elG8bH2MsAYTii7OHuQQqorTUiMK6ZCH/dk=");
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        assert_eq!(vault.get_typed::<u16>("PORT").unwrap(), 8080);
+        assert!(vault.get_typed::<bool>("DEBUG").unwrap());
+        assert_eq!(vault.get_typed::<url::Url>("URL").unwrap().path(), "/app");
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn get_typed_invalid() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "9gAS9K5KNxQG6o8Jx2m9OMPro7ieKf8S2W5Ww0LW0iYQfyr06FzEpeeobdOHR8wYY+OpP0LIdQRObLWyFOU3a9rmiSVVBD14elG8bH2MsAYTii7OHuQQqorTUiMK6ZCH/dk=");
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let result = vault.get_typed::<u16>("URL");
+        assert!(matches!(result, Err(Error::ParseValueError { ref key, .. }) if key == "URL"));
+
+        let result = vault.get_typed::<bool>("PORT");
+        assert!(matches!(result, Err(Error::ParseValueError { ref key, .. }) if key == "PORT"));
+
+        let result = vault.get_typed::<u16>("MISSING");
+        assert!(matches!(result, Err(Error::RequiredVariableMissing(ref key)) if key == "MISSING"));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn keys_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        assert_eq!(vault.keys().unwrap(), vec!["ALPHA", "BETA"]);
+        assert_eq!(vault.values_count().unwrap(), 2);
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn keys_missing_key() {
+        let vault = Vault::from_bytes("DOTENV_VAULT_PRODUCTION=\"\"", "");
+        let vault = Vault { key: None, ..vault };
+
+        assert!(matches!(vault.keys(), Err(Error::KeyNotFound)));
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn parse_interpolate() {
+        let ciphertext = Vault::encrypt(
+            b"URL=\"http://${INTERPOLATE_HOST}/app\"\nINTERPOLATE_HOST=localhost",
+            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00",
+            false,
+        )
+        .unwrap();
+        let contents = format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext);
+        let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+        let parsed = Vault::from_bytes(contents.as_str(), key)
+            .parse_memory()
+            .unwrap();
+        let map: HashMap<String, String> = dotenvy::from_read_iter(&parsed[..])
+            .collect::<dotenvy::Result<_>>()
+            .unwrap();
+        assert_eq!(map["URL"], "http:///app");
+
+        let vault = Vault {
+            config: VaultConfig {
+                interpolate: true,
+                ..VaultConfig::default()
+            },
+            ..Vault::from_bytes(contents.as_str(), key)
+        };
+        let parsed = vault.parse_memory().unwrap();
+        let map: HashMap<String, String> = dotenvy::from_read_iter(&parsed[..])
+            .collect::<dotenvy::Result<_>>()
+            .unwrap();
+        assert_eq!(map["URL"], "http://localhost/app");
+    }
+
+    #[test]
+    fn parse_strip_outer_quotes() {
+        let ciphertext = Vault::encrypt(
+            b"DOUBLE=\"\\\"double \\\"quoted\\\" value\\\"\"\nSINGLE=\"'single'\"\nUNQUOTED=unquoted\nMISMATCHED=\"\\\"mismatched'\"\nLONE='\"'",
+            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00",
+            false,
+        )
+        .unwrap();
+        let contents = format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext);
+        let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+        let map = Vault::from_bytes(contents.as_str(), key).to_map().unwrap();
+        assert_eq!(map["DOUBLE"], "\"double \"quoted\" value\"");
+        assert_eq!(map["SINGLE"], "'single'");
+        assert_eq!(map["UNQUOTED"], "unquoted");
+        assert_eq!(map["MISMATCHED"], "\"mismatched'");
+        assert_eq!(map["LONE"], "\"");
+
+        let vault = Vault {
+            config: VaultConfig {
+                strip_outer_quotes: true,
+                ..VaultConfig::default()
+            },
+            ..Vault::from_bytes(contents.as_str(), key)
+        };
+        let map = vault.to_map().unwrap();
+        assert_eq!(map["DOUBLE"], "double \"quoted\" value");
+        assert_eq!(map["SINGLE"], "single");
+        assert_eq!(map["UNQUOTED"], "unquoted");
+        assert_eq!(map["MISMATCHED"], "\"mismatched'");
+        assert_eq!(map["LONE"], "\"");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    #[cfg(feature = "tracing")]
+    #[tracing_test::traced_test]
+    fn load_tracing_spans() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        assert!(vault.load().is_ok());
+
+        assert!(logs_contain("vault_find"));
+        assert!(logs_contain("vault.exists=true"));
+        assert!(logs_contain("vault_decrypt"));
+        assert!(logs_contain("vault.decrypted_bytes=42"));
+        assert!(logs_contain("vault.key_count=2"));
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn access_log_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let vault = Vault {
+            config: VaultConfig {
+                audit: true,
+                ..VaultConfig::default()
+            },
+            ..Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production&key_id=v2")
+        };
+
+        assert!(vault.load().is_ok());
+        assert!(vault.load().is_ok());
+
+        let log = vault.access_log();
+        assert_eq!(log.len(), 2);
+        assert!(log.iter().all(|record| record.operation == "load"
+            && record.environment == "production"
+            && record.success));
+        assert!(log[0].timestamp <= log[1].timestamp);
+        assert!(log
+            .iter()
+            .all(|record| record.key_fingerprint.as_deref() == Some("bfeade18ad3ef8be")));
+        assert!(log
+            .iter()
+            .all(|record| record.key_id.as_deref() == Some("v2")));
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn key_fingerprint_ok() {
+        let uri = Vault::key_fingerprint("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let key = Vault::key_fingerprint(
+            "key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00",
+        );
+
+        assert_eq!(uri.unwrap(), "bfeade18ad3ef8be");
+        assert_eq!(key.unwrap(), "bfeade18ad3ef8be");
+    }
+
+    #[test]
+    fn key_fingerprint_invalid() {
+        assert!(matches!(
+            Vault::key_fingerprint("key_1234"),
+            Err(Error::InvalidKey)
+        ));
+        assert!(matches!(
+            Vault::key_fingerprint(
+                "key_XXcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00"
+            ),
+            Err(Error::HexError(_))
+        ));
+    }
+
+    #[test]
+    fn last_key_fingerprint_ok() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_0000000000000000000000000000000000000000000000000000000000000000@dotenv.local/vault/.env.vault?environment=production,dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        assert_eq!(vault.last_key_fingerprint(), None);
+
+        assert!(vault.to_map().is_ok());
+        assert_eq!(
+            vault.last_key_fingerprint().as_deref(),
+            Some("bfeade18ad3ef8be")
+        );
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn import_from_hashmap_round_trip() {
+        let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+        let map: HashMap<String, String> = [
+            ("IMPORT_PLAIN", "omega"),
+            ("IMPORT_SPACES", "hello world"),
+            ("IMPORT_QUOTES", "he said \"hi\" to 'them'"),
+            ("IMPORT_SPECIAL", "$HOME \\ #not a comment"),
+            ("IMPORT_MULTI", "line one\nline two"),
+            ("IMPORT_EMPTY", ""),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        for name in map.keys() {
+            env::remove_var(name);
+        }
+
+        let entry = Vault::import_from_hashmap(&map, key).unwrap();
+        assert!(entry.starts_with("DOTENV_VAULT_PRODUCTION=\""));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        std::fs::write(&vault_path, format!("{}\n", entry)).unwrap();
+
+        assert!(Vault::from_path(&vault_path, key).load().is_ok());
+        for (name, value) in &map {
+            assert_eq!(&env::var(name).unwrap(), value);
+            env::remove_var(name);
+        }
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn import_from_hashmap_missing_environment() {
+        let result = Vault::import_from_hashmap(
+            &HashMap::new(),
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault",
+        );
+
+        assert!(matches!(result, Err(Error::MissingEnvironment)));
+    }
+
+    #[test]
+    fn generate_key_round_trip() {
+        let (key_uri, hex_key) = Vault::generate_key("production");
+        assert_eq!(hex_key.len(), 64);
+        assert_ne!(
+            Vault::generate_key("production"),
+            (key_uri.clone(), hex_key.clone())
+        );
+
+        let url = url::Url::parse(&key_uri).unwrap();
+        let host = url.host_str().unwrap();
+        assert_eq!(host.len(), 36);
+        assert_eq!(host.as_bytes()[14], b'4');
+
+        let vault = Vault::new();
+        let (key, environment) = vault.instructions(&key_uri).unwrap();
+        assert_eq!(key, format!("key_{}", hex_key));
+        assert_eq!(environment, "DOTENV_VAULT_PRODUCTION");
+
+        let ciphertext = Vault::encrypt(b"ALPHA=\"zeta\"", &hex_key, false).unwrap();
+        let decrypted = vault
+            .decrypt(ciphertext, key[key.len() - 64..].to_string())
+            .unwrap();
+        assert_eq!(decrypted, b"ALPHA=\"zeta\"");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn get_or_env_sources() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        env::set_var("ALPHA", "from env");
+        env::set_var("GET_OR_ENV_ONLY_ENV", "from env");
+        env::remove_var("GET_OR_ENV_NEITHER");
+
+        assert_eq!(vault.get_or_env("ALPHA").unwrap().as_deref(), Some("omega"));
+        assert_eq!(
+            vault.get_or_env("GET_OR_ENV_ONLY_ENV").unwrap().as_deref(),
+            Some("from env")
+        );
+        assert_eq!(vault.get_or_env("GET_OR_ENV_NEITHER").unwrap(), None);
+        assert_eq!(env::var("ALPHA").unwrap(), "from env");
+
+        env::remove_var("ALPHA");
+        env::remove_var("GET_OR_ENV_ONLY_ENV");
+    }
+
+    #[test]
+    fn get_all_matching_patterns() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let matching = |pattern: &str| {
+            let mut keys: Vec<String> = vault
+                .get_all_matching(pattern)
+                .unwrap()
+                .into_keys()
+                .collect();
+            keys.sort();
+            keys
+        };
+
+        assert_eq!(matching("*"), ["ALPHA", "BETA"]);
+        assert_eq!(matching("*A"), ["ALPHA", "BETA"]);
+        assert_eq!(matching("AL*"), ["ALPHA"]);
+        assert_eq!(matching("?ETA"), ["BETA"]);
+        assert!(matching("?ALPHA").is_empty());
+        assert_eq!(matching("ALPHA"), ["ALPHA"]);
+        assert!(matching("ALPH").is_empty());
+
+        let alpha = vault.get_all_matching("ALPHA").unwrap();
+        assert_eq!(alpha.get("ALPHA").map(String::as_str), Some("omega"));
+    }
+
+    #[test]
+    fn last_key_id_ok() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_0000000000000000000000000000000000000000000000000000000000000000@dotenv.local/vault/.env.vault?environment=production&key_id=v1,dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production&key_id=v2",
+        );
+        assert_eq!(vault.last_key_id(), None);
+
+        assert!(vault.to_map().is_ok());
+        assert_eq!(vault.last_key_id().as_deref(), Some("v2"));
+        assert_eq!(
+            vault.last_key_fingerprint().as_deref(),
+            Some("bfeade18ad3ef8be")
+        );
+    }
+
+    #[test]
+    fn key_id_instructions_round_trip() {
+        let dotenv_key = "dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&key_id=2024-06%2Frotated";
+        let vault = Vault::new();
+
+        let (key, environment) = vault.instructions(dotenv_key).unwrap();
+        assert_eq!(key, "key_1234");
+        assert_eq!(environment, "DOTENV_VAULT_PRODUCTION");
+        assert_eq!(
+            Vault::key_id(dotenv_key).unwrap().as_deref(),
+            Some("2024-06/rotated")
+        );
+
+        assert_eq!(
+            Vault::key_id("dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production")
+                .unwrap(),
+            None
+        );
+        assert_eq!(Vault::key_id("key_1234").unwrap(), None);
+    }
+
+    #[test]
+    fn validate_nonce_uniqueness_ok() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"\nDOTENV_VAULT_STAGING=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let mut seen_nonces = HashSet::new();
+
+        assert!(vault.validate_nonce_uniqueness(&mut seen_nonces).is_ok());
+        assert_eq!(seen_nonces.len(), 2);
+        assert!(matches!(
+            vault.validate_nonce_uniqueness(&mut seen_nonces),
+            Err(Error::NonceReuse)
+        ));
+    }
+
+    #[test]
+    fn validate_nonce_uniqueness_salted() {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let key = "dotenv://:correct%20horse@dotenv.local/vault/.env.vault?environment=production&environment=staging&kdf=pbkdf2&iterations=1000";
+        let production =
+            Vault::encrypt_with_passphrase(b"ALPHA=\"zeta\"", "correct horse", 1000, false)
+                .unwrap();
+        let staging =
+            Vault::encrypt_with_passphrase(b"ALPHA=\"zeta\"", "correct horse", 1000, false)
+                .unwrap();
+
+        let vault = Vault::from_bytes(
+            format!(
+                "DOTENV_VAULT_PRODUCTION=\"{}\"\nDOTENV_VAULT_STAGING=\"{}\"",
+                production, staging
+            ),
+            key,
+        );
+        assert!(vault.validate_nonce_uniqueness(&mut HashSet::new()).is_ok());
+
+        // A different salt in front of a reused nonce
+        let mut reused = general_purpose::STANDARD.decode(&production).unwrap();
+        reused[0] ^= 0xff;
+        let vault = Vault::from_bytes(
+            format!(
+                "DOTENV_VAULT_PRODUCTION=\"{}\"\nDOTENV_VAULT_STAGING=\"{}\"",
+                production,
+                general_purpose::STANDARD.encode(reused)
+            ),
+            key,
+        );
+        assert!(matches!(
+            vault.validate_nonce_uniqueness(&mut HashSet::new()),
+            Err(Error::NonceReuse)
+        ));
+    }
+
+    #[test]
+    fn to_map_nonce_reuse() {
+        let contents = "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"\nDOTENV_VAULT_STAGING=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"";
+        let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+        let unchecked = Vault::from_bytes(contents, key);
+        let checked = Vault {
+            config: VaultConfig {
+                detect_nonce_reuse: true,
+                ..VaultConfig::default()
+            },
+            ..Vault::from_bytes(contents, key)
+        };
+
+        assert!(unchecked.to_map().is_ok());
+        assert!(matches!(checked.to_map(), Err(Error::NonceReuse)));
+    }
+
+    #[test]
+    fn load_all_environments_ok() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"\nDOTENV_VAULT_STAGING=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"\nDOTENV_VAULT_CI=\"bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        )
+        .with_logging_disabled();
+
+        let environments = vault.load_all_environments().unwrap();
+
+        assert_eq!(environments.len(), 2);
+        assert_eq!(environments["production"]["ALPHA"], "omega");
+        assert_eq!(environments["production"]["BETA"], "gamma");
+        assert_eq!(environments["staging"]["ALPHA"], "zeta");
+        assert!(!environments.contains_key("ci"));
+    }
+
+    #[test]
+    fn load_all_environments_missing_key() {
+        let vault = Vault {
+            key: None,
+            ..Vault::from_bytes("", "")
+        };
+
+        assert!(matches!(
+            vault.load_all_environments(),
+            Err(Error::KeyNotFound)
+        ));
+    }
+
+    #[test]
+    fn preflight_check_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        assert_eq!(
+            vault.preflight_check().unwrap(),
+            PreflightReport {
+                key_valid: true,
+                vault_found: true,
+                permissions_ok: true,
+                environments: vec!["production".to_string()],
+                warnings: Vec::new(),
+            }
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn preflight_check_problems() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==");
+
+        let report = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production").preflight_check().unwrap();
+        assert!(report.key_valid);
+        assert!(report.vault_found);
+        assert!(report.environments.is_empty());
+        assert_eq!(
+            report.warnings,
+            ["No environment can be decrypted with your DOTENV_KEY"]
+        );
+
+        let report = Vault::from_path(
+            &vault_path,
+            "https://:key_1234@dotenv.local/vault/.env.vault?environment=production",
+        )
+        .preflight_check()
+        .unwrap();
+        assert!(!report.key_valid);
+        assert!(report.vault_found);
+        assert_eq!(report.warnings, [Error::InvalidScheme.to_string()]);
+
+        let report = Vault::from_path(
+            tmp.path().join("missing.env.vault"),
+            "dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=production",
+        )
+        .preflight_check()
+        .unwrap();
+        assert!(report.key_valid);
+        assert!(!report.vault_found);
+        assert_eq!(report.warnings, [Error::VaultNotFound.to_string()]);
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn preflight_check_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+        std::fs::set_permissions(&vault_path, std::fs::Permissions::from_mode(0o666)).unwrap();
+
+        let report = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production").preflight_check().unwrap();
+        assert!(!report.permissions_ok);
+        assert_eq!(report.environments, ["production"]);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("is writable by other users"));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn stats_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let stats = vault.stats().unwrap();
+
+        assert_eq!(stats.path, Some(vault_path));
+        assert_eq!(stats.environment.as_deref(), Some("production"));
+        assert_eq!(stats.variable_count, 2);
+        assert!(stats.decryption_duration > Duration::ZERO);
+        assert!(!stats.fallback_used);
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn stats_from_memory() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let stats = vault.stats().unwrap();
+
+        assert_eq!(stats.path, None);
+        assert_eq!(stats.environment.as_deref(), Some("production"));
+        assert_eq!(stats.variable_count, 2);
+        assert!(!stats.fallback_used);
+    }
+
+    #[test]
+    fn stats_display() {
+        let stats = VaultStats {
+            path: Some(PathBuf::from("/app/.env.vault")),
+            environment: Some("production".to_string()),
+            variable_count: 2,
+            decryption_duration: Duration::from_micros(3_900),
+            fallback_used: false,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "Loaded 2 variables from production (/app/.env.vault) in 3ms"
+        );
+
+        let stats = VaultStats {
+            path: None,
+            ..stats
+        };
+        assert_eq!(
+            stats.to_string(),
+            "Loaded 2 variables from production (memory) in 3ms"
+        );
+
+        let stats = VaultStats {
+            path: None,
+            environment: None,
+            variable_count: 3,
+            decryption_duration: Duration::from_millis(12),
+            fallback_used: true,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "Loaded 3 variables from .env (fallback) in 12ms"
+        );
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn stats_fallback() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut env_file = File::create(tmp.path().join(".env")).unwrap();
+        env_file
+            .write_all("ALPHA=\"from .env\"\nBETA=beta\nGAMMA=gamma".as_bytes())
+            .unwrap();
+        env_file.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+        env::remove_var("DOTENV_KEY");
+
+        let stats = Vault::new().with_logging_disabled().stats();
+
+        env::set_current_dir(cwd).unwrap();
+
+        let stats = stats.unwrap();
+        assert_eq!(stats.path, None);
+        assert_eq!(stats.environment, None);
+        assert_eq!(stats.variable_count, 3);
+        assert!(stats.fallback_used);
+        assert_eq!(env::var("GAMMA"), Err(env::VarError::NotPresent));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_with_env_filter_predicates() {
+        // Encrypts `ALPHA="zeta"`, `QUOTED='he said "hi" to $USER'` and
+        // `MULTI="line one\nline two"`
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"urFzqN5UkXsh6nlSGgg0u5X0k/Qt2wCn+4QNr1r7hQEPUTcbxy/StjiGTVqopF3jZu2vIZmyW0zcML1uwjcjrb/T6YDHIvS7oCUSO7H9hLKH7nPq+i8vhw5RpY20fGL0wC0=\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let names = ["ALPHA", "QUOTED", "MULTI"];
+        for name in names {
+            env::remove_var(name);
+        }
+        let loaded = |predicate: &dyn Fn(&str) -> bool| {
+            vault.load_with_env_filter(predicate).unwrap();
+            let loaded: Vec<&str> = names
+                .into_iter()
+                .filter(|name| env::var_os(name).is_some())
+                .collect();
+            for name in names {
+                env::remove_var(name);
+            }
+            loaded
+        };
+
+        assert_eq!(loaded(&|key| key.starts_with("AL")), ["ALPHA"]);
+        assert_eq!(loaded(&|key| key.ends_with("ED")), ["QUOTED"]);
+        assert_eq!(loaded(&|key| key.contains('L')), ["ALPHA", "MULTI"]);
+        assert!(loaded(&|_| false).is_empty());
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_into_process_env_guarded_blocked() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+
+        let result = vault.load_into_process_env_guarded(&["BETA"]);
+        assert!(matches!(result, Err(Error::BlockedVariableName(ref key)) if key == "BETA"));
+        assert_eq!(env::var("ALPHA"), Err(env::VarError::NotPresent));
+        assert_eq!(env::var("BETA"), Err(env::VarError::NotPresent));
+
+        assert!(vault
+            .load_into_process_env_guarded(DEFAULT_BLOCKLIST)
+            .is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "omega");
+        assert_eq!(env::var("BETA").unwrap(), "gamma");
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_with_callback_ok() {
+        env::remove_var("ALPHA");
+        env::set_var("BETA", "existing");
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let seen = Mutex::new(Vec::new());
+        let result = vault.load_with_callback(|key, value| {
+            assert!(env::var(key).is_err() || key == "BETA");
+            seen.lock()
+                .unwrap()
+                .push((key.to_string(), value.to_string()));
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(
+            seen.into_inner().unwrap(),
+            [
+                ("ALPHA".to_string(), "omega".to_string()),
+                ("BETA".to_string(), "gamma".to_string())
+            ]
+        );
+        assert_eq!(env::var("ALPHA").unwrap(), "omega");
+        assert_eq!(env::var("BETA").unwrap(), "existing");
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_with_priority_ok() {
+        env::set_var("ALPHA", "beta");
+        env::remove_var("DELTA");
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let overrides = HashMap::from([
+            ("ALPHA".to_string(), "gamma".to_string()),
+            ("DELTA".to_string(), "epsilon".to_string()),
+        ]);
+
+        assert!(vault.load_with_priority(&overrides).is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "gamma");
+        assert_eq!(env::var("DELTA").unwrap(), "epsilon");
+
+        env::remove_var("ALPHA");
+        env::remove_var("DELTA");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_or_default_not_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::set_var("ALPHA", "existing");
+        env::remove_var("BETA");
+
+        let defaults = HashMap::from([
+            ("ALPHA".to_string(), "default".to_string()),
+            ("BETA".to_string(), "default".to_string()),
+        ]);
+        let result = Vault::new()
+            .with_logging_disabled()
+            .load_or_default(defaults);
+
+        env::set_current_dir(cwd).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "existing");
+        assert_eq!(env::var("BETA").unwrap(), "default");
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_or_default_decrypt_error() {
+        env::remove_var("ALPHA");
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let defaults = HashMap::from([("ALPHA".to_string(), "default".to_string())]);
+
+        let result = vault.load_or_default(defaults);
+        // A vault no key can decrypt is reported as an invalid key
+        assert!(matches!(result, Err(Error::InvalidKey)));
+        assert!(env::var("ALPHA").is_err());
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn default_loads_like_new() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_vault(&tmp.path().join(".env.vault"), "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let mut loaded = Vec::new();
+        for vault in [Vault::default(), Vault::new()] {
+            env::remove_var("ALPHA");
+            env::remove_var("BETA");
+            assert!(vault.load().is_ok());
+            loaded.push((env::var("ALPHA").unwrap(), env::var("BETA").unwrap()));
+        }
+
+        env::set_current_dir(cwd).unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+
+        assert_eq!(loaded[0], ("omega".to_string(), "gamma".to_string()));
+        assert_eq!(loaded[0], loaded[1]);
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn from_vault_string_load() {
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+
+        let mut vault = Vault::from_vault_string(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".to_string(),
+        );
+        // The in-memory contents take precedence over the path
+        vault.path = Some(PathBuf::from("missing/.env.vault"));
+
+        assert!(vault.load().is_ok());
+        assert_eq!(env::var("ALPHA").unwrap(), "omega");
+        assert_eq!(env::var("BETA").unwrap(), "gamma");
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+    }
+
+    /// Write a shared vault with `ALPHA="omega"` and `BETA="gamma"` and a service vault with
+    /// `ALPHA="from staging"` and `EXTRA="only staging"`, returning `(service, shared)`
+    fn write_merge_vaults(dir: &Path) -> (Vault, Vault) {
+        write_vault(&dir.join("shared.env.vault"), "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+        std::fs::write(
+            dir.join("service.env.vault"),
+            "DOTENV_VAULT_STAGING=\"u1ir+wuWtAmepyLx6DlYMs9a5sXunEiC+AP3sbFH6a53H5r2597eZP8U6maXleJ9w3pXcQEb2Kr2QFqIMu0MWNjTzohP\"",
+        )
+        .unwrap();
+
+        let shared = Vault::from_path(dir.join("shared.env.vault"), "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let service = Vault::from_path(dir.join("service.env.vault"), "dotenv://:key_01b08fe1173b781cce5fd1a18178c5cacdf3bb0845a8aa1b8089ac0751f7ed9c@dotenv.local/vault/.env.vault?environment=staging");
+        (service, shared)
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_merge_priority() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (service, shared) = write_merge_vaults(tmp.path());
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        env::set_var("EXTRA", "existing");
+
+        let result = service.load_merge(&shared);
+        let vars: Vec<_> = ["ALPHA", "BETA", "EXTRA"].map(env::var).into();
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        env::remove_var("EXTRA");
+
+        assert!(result.is_ok());
+        assert_eq!(vars[0].as_deref(), Ok("from staging"));
+        assert_eq!(vars[1].as_deref(), Ok("gamma"));
+        assert_eq!(vars[2].as_deref(), Ok("existing"));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_merge_override_priority() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (service, shared) = write_merge_vaults(tmp.path());
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        env::set_var("EXTRA", "existing");
+
+        let result = service.load_merge_override(&shared);
+        let vars: Vec<_> = ["ALPHA", "BETA", "EXTRA"].map(env::var).into();
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        env::remove_var("EXTRA");
+
+        assert!(result.is_ok());
+        assert_eq!(vars[0].as_deref(), Ok("omega"));
+        assert_eq!(vars[1].as_deref(), Ok("gamma"));
+        assert_eq!(vars[2].as_deref(), Ok("only staging"));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_into_scope_restores() {
+        env::remove_var("ALPHA");
+        env::set_var("BETA", "existing");
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        let vault = Vault {
+            config: VaultConfig {
+                audit: true,
+                ..VaultConfig::default()
+            },
+            ..vault
+        };
+        let seen = vault.load_into_scope(|| {
+            env::set_var("BETA", "changed");
+            (env::var("ALPHA").unwrap(), env::var("BETA").unwrap())
+        });
+
+        assert_eq!(seen.unwrap(), ("omega".to_string(), "changed".to_string()));
+        assert!(env::var("ALPHA").is_err());
+        assert_eq!(env::var("BETA").unwrap(), "existing");
+
+        // The vault is decrypted only once
+        assert_eq!(vault.access_log().len(), 1);
+
+        env::remove_var("BETA");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn try_load_nothing_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+        env::remove_var("DOTENV_KEY");
+
+        let loaded = Vault::new().with_logging_disabled().try_load();
+
+        env::set_current_dir(cwd).unwrap();
+
+        assert!(loaded.is_none());
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn try_load_found() {
+        env::remove_var("ALPHA");
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        assert!(matches!(vault.try_load(), Some(Ok(()))));
+        assert_eq!(env::var("ALPHA").unwrap(), "zeta");
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+        assert!(matches!(vault.try_load(), Some(Err(Error::InvalidKey))));
+
+        env::remove_var("ALPHA");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_environment_raw_key() {
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        env::set_var("DOTENV_ENVIRONMENT", "production");
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_DEVELOPMENT=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"\nDOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00",
+        );
+        let result = vault.load_environment(&env::var("DOTENV_ENVIRONMENT").unwrap());
+        let (alpha, beta) = (env::var("ALPHA"), env::var("BETA"));
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+        env::remove_var("DOTENV_ENVIRONMENT");
+
+        assert!(result.is_ok());
+        assert_eq!(alpha.unwrap(), "omega");
+        assert_eq!(beta.unwrap(), "gamma");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_environment_errors() {
+        env::set_var("DOTENV_ENVIRONMENT", "production");
+        let environment = env::var("DOTENV_ENVIRONMENT").unwrap();
+
+        // The environment of a full dotenv key uri is ignored
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_STAGING=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=staging",
+        );
+        assert!(matches!(
+            vault.load_environment(&environment),
+            Err(Error::EnvironmentNotFound { requested, available })
+                if requested == "DOTENV_VAULT_PRODUCTION" && available == ["staging"]
+        ));
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==\"",
+            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00",
+        );
+        assert!(matches!(
+            vault.load_environment(&environment),
+            Err(Error::InvalidKey)
+        ));
+
+        // A raw key whose last 64 bytes split a multi-byte character
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            &format!("{}a", "é".repeat(40)),
+        );
+        assert!(matches!(
+            vault.load_environment(&environment),
+            Err(Error::InvalidKey)
+        ));
+
+        env::remove_var("DOTENV_ENVIRONMENT");
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn iter_matches_to_map() {
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        let mut map = HashMap::new();
+        for variable in vault.iter().unwrap() {
+            let (key, value) = variable.unwrap();
+            map.insert(key, value);
+        }
+        assert_eq!(map, vault.to_map().unwrap());
+        assert_eq!(map.len(), 2);
+
+        // The vault is not consumed by iterating
+        assert_eq!(vault.iter().unwrap().count(), 2);
+        let result = vault.load();
+        let alpha = env::var("ALPHA");
+
+        env::remove_var("ALPHA");
+        env::remove_var("BETA");
+
+        assert!(result.is_ok());
+        assert_eq!(alpha.unwrap(), "omega");
+    }
+
+    #[test]
+    fn keys_iter_and_values_iter() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        let keys = vault
+            .keys_iter()
+            .unwrap()
+            .collect::<Result<BTreeSet<_>>>()
+            .unwrap();
+        assert_eq!(
+            keys,
+            BTreeSet::from(["ALPHA".to_string(), "BETA".to_string()])
+        );
+
+        let values = vault
+            .values_iter()
+            .unwrap()
+            .collect::<Result<BTreeSet<_>>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            BTreeSet::from(["omega".to_string(), "gamma".to_string()])
+        );
+    }
+
+    #[test]
+    fn iter_invalid_key() {
+        let vault = Vault::from_bytes(
+            "DOTENV_VAULT_PRODUCTION=\"bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==\"",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        assert!(matches!(vault.iter(), Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    fn access_log_disabled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let vault_path = tmp.path().join(".env.vault");
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        assert!(vault.to_map().is_ok());
+        assert!(vault.access_log().is_empty());
+
+        tmp.close().unwrap();
+    }
+
+    fn write_vault(path: &std::path::Path, ciphertext: &str) {
+        let mut vault = File::create(path).unwrap();
+        vault
+            .write_all(format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext).as_bytes())
+            .unwrap();
+        vault.sync_all().unwrap();
+    }
+
+    #[test]
+    fn parse_per_environment_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_vault(
+            &tmp.path().join(".env.production.vault"),
+            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R",
+        );
+        write_vault(
+            &tmp.path().join(".env.vault"),
+            "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+        );
+
+        let vault = Vault {
+            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
+            path: Some(tmp.path().join(".env.vault")),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_ok());
+        assert_eq!(
+            parsed.unwrap(),
+            "# development@v6\nALPHA=\"zeta\"".as_bytes()
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn parse_per_environment_file_from_environment() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_vault(
+            &tmp.path().join(".env.staging.vault"),
+            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R",
+        );
+        write_vault(
+            &tmp.path().join(".env.vault"),
+            "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+        );
+
+        let vault = Vault {
+            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
+            path: Some(tmp.path().join(".env.vault")),
+            contents: None,
+            environment: Some("staging".into()),
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_ok());
+        assert_eq!(
+            parsed.unwrap(),
+            "# development@v6\nALPHA=\"zeta\"".as_bytes()
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn parse_per_environment_file_missing() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_vault(
+            &tmp.path().join(".env.vault"),
+            "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+        );
+
+        let vault = Vault {
+            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
+            path: Some(tmp.path().join(".env.vault")),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config: VaultConfig::default(),
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_ok());
+        assert_eq!(
+            parsed.unwrap(),
+            "# production@v6\nALPHA=\"omega\"\nBETA=\"gamma\"".as_bytes()
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn parse_per_environment_file_disabled() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_vault(
+            &tmp.path().join(".env.production.vault"),
+            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R",
+        );
+        write_vault(
+            &tmp.path().join(".env.vault"),
+            "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+        );
+
+        let config = VaultConfig {
+            use_per_environment_files: false,
+            ..VaultConfig::default()
+        };
+        let vault = Vault {
+            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
+            path: Some(tmp.path().join(".env.vault")),
+            contents: None,
+            environment: None,
+            target_environment: None,
+            config,
+            access_log: Arc::default(),
+            last_key_fingerprint: Arc::default(),
+            last_key_id: Arc::default(),
+        };
+        let parsed = vault.parse();
+
+        assert!(parsed.is_ok());
+        assert_eq!(
+            parsed.unwrap(),
+            "# production@v6\nALPHA=\"omega\"\nBETA=\"gamma\"".as_bytes()
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn find_vault_in_ancestors_at_repository_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join(".git")).unwrap();
+        let nested = tmp.path().join("crates").join("app").join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        write_vault(&tmp.path().join(".env.vault"), "");
+
+        assert_eq!(
+            Vault::find_vault_in_ancestors(&nested),
+            Some(tmp.path().join(".env.vault"))
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn find_vault_in_ancestors_closest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let nested = tmp.path().join("crates").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        write_vault(&tmp.path().join(".env.vault"), "");
+        write_vault(&tmp.path().join("crates").join(".env.vault"), "");
+
+        assert_eq!(
+            Vault::find_vault_in_ancestors(&nested),
+            Some(tmp.path().join("crates").join(".env.vault"))
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn find_vault_in_ancestors_stops_at_repository_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repository = tmp.path().join("repository");
+        let nested = repository.join("app");
+        std::fs::create_dir_all(repository.join(".git")).unwrap();
+        std::fs::create_dir_all(&nested).unwrap();
+        write_vault(&tmp.path().join(".env.vault"), "");
+
+        assert_eq!(Vault::find_vault_in_ancestors(&nested), None);
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn find_key_in_ancestors_environment() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join(".git")).unwrap();
+        let nested = tmp.path().join("crates").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            tmp.path().join(".env.keys"),
+            "DOTENV_KEY_DEVELOPMENT=\"dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=development\"\nDOTENV_KEY_PRODUCTION=\"dotenv://:key_5678@dotenv.local/vault/.env.vault?environment=production\"\n",
+        )
+        .unwrap();
+
+        env::remove_var("DOTENV_ENVIRONMENT");
+        assert_eq!(
+            Vault::find_key_in_ancestors(&nested).as_deref(),
+            Some("dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=development")
+        );
+
+        env::set_var("DOTENV_ENVIRONMENT", "production");
+        assert_eq!(
+            Vault::find_key_in_ancestors(&nested).as_deref(),
+            Some("dotenv://:key_5678@dotenv.local/vault/.env.vault?environment=production")
+        );
+
+        env::set_var("DOTENV_ENVIRONMENT", "staging");
+        assert_eq!(Vault::find_key_in_ancestors(&nested), None);
+
+        env::remove_var("DOTENV_ENVIRONMENT");
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn find_key_in_ancestors_stops_at_repository_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repository = tmp.path().join("repository");
+        std::fs::create_dir_all(repository.join(".git")).unwrap();
+        std::fs::write(
+            tmp.path().join(".env.keys"),
+            "DOTENV_KEY_DEVELOPMENT=\"dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=development\"",
+        )
+        .unwrap();
+
+        env::remove_var("DOTENV_ENVIRONMENT");
+        assert_eq!(Vault::find_key_in_ancestors(&repository), None);
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn load_key_from_env_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join(".git")).unwrap();
+        let nested = tmp.path().join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            tmp.path().join(".env.keys"),
+            "DOTENV_KEY_PRODUCTION=\"dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production\"",
+        )
+        .unwrap();
+        write_vault(&nested.join(".env.vault"), "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&nested).unwrap();
+        env::remove_var("DOTENV_KEY");
+        env::set_var("DOTENV_ENVIRONMENT", "production");
+
+        let map = Vault::new().to_map();
+
+        env::set_current_dir(cwd).unwrap();
+        env::remove_var("DOTENV_ENVIRONMENT");
+
+        assert_eq!(map.unwrap()["ALPHA"], "omega");
+        tmp.close().unwrap();
+    }
 
-        if url.scheme() != "dotenv" {
-            return Err(Error::InvalidScheme);
-        }
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn to_map_git_root_discovery() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join(".git")).unwrap();
+        let nested = tmp.path().join("crates").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        write_vault(
+            &tmp.path().join(".env.vault"),
+            "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+        );
 
-        let key = match url.password() {
-            Some(key) => key.to_string(),
-            None => return Err(Error::MissingKey),
-        };
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&nested).unwrap();
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
 
-        let environment = match url.query_pairs().find(|(k, _)| k == "environment") {
-            Some((_, environment)) => environment.to_string(),
-            None => return Err(Error::MissingEnvironment),
+        let config = VaultConfig {
+            git_root_discovery: true,
+            ..VaultConfig::default()
         };
+        let discovered = Vault::new_with_config(config).to_map();
+        let missing = Vault::new().to_map();
+
+        env::remove_var("DOTENV_KEY");
+        env::set_current_dir(cwd).unwrap();
 
-        let environment_key = format!("DOTENV_VAULT_{}", environment.to_uppercase());
-        Ok((key, environment_key))
+        let discovered = discovered.unwrap();
+        assert_eq!(discovered["ALPHA"], "omega");
+        assert_eq!(discovered["BETA"], "gamma");
+        assert!(missing.is_err());
+
+        tmp.close().unwrap();
     }
 
-    /// Parse the *.env.vault* file into a `Vec<u8>`
-    ///
-    /// # Returns
-    /// A `Result` containing a `Vec<u8>` of the decrypted vault contents
-    fn parse(&self) -> Result<Vec<u8>> {
-        let keys = match self.key.as_ref() {
-            Some(key) => key,
-            None => return Err(Error::KeyNotFound),
-        };
+    #[test]
+    #[serial] // Run serially due to env modifications
+    fn values_count_git_root_discovery() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join(".git")).unwrap();
+        let nested = tmp.path().join("crates").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        write_vault(
+            &tmp.path().join(".env.vault"),
+            "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+        );
 
-        let path = match self.path.as_ref() {
-            Some(path) => path,
-            None => return Err(Error::VaultNotFound),
-        };
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&nested).unwrap();
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
 
-        for key in keys.split(',') {
-            if let Ok(decrypted) = self
-                .instructions(key)
-                .and_then(|(k, e)| {
-                    let vault = dotenvy::from_path_iter(path)?;
-                    let maybe_ciphertext = vault.into_iter().find(|item| match item {
-                        Ok((k, _)) => k == &e,
-                        _ => false,
-                    });
-                    let ciphertext = match maybe_ciphertext {
-                        Some(Ok((_, c))) => c,
-                        _ => return Err(Error::EnvironmentNotFound(e)),
-                    };
-
-                    Ok((ciphertext, k))
-                })
-                .and_then(|(c, k)| self.decrypt(c, k))
-            {
-                return Ok(decrypted);
-            }
-        }
+        let vault = Vault::new_with_config(VaultConfig {
+            git_root_discovery: true,
+            ..VaultConfig::default()
+        });
+        let values_count = vault.values_count();
+        let len = vault.len();
+        let missing = Vault::new().with_logging_disabled().keys();
 
-        Err(Error::InvalidKey)
-    }
-}
+        env::remove_var("DOTENV_KEY");
+        env::set_current_dir(cwd).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use serial_test::serial;
-    use std::{fs::File, io::prelude::*};
+        assert_eq!(values_count.unwrap(), 2);
+        assert_eq!(len.unwrap(), 2);
+        assert!(matches!(missing, Err(Error::VaultNotFound)));
 
-    use super::*;
+        tmp.close().unwrap();
+    }
 
     #[test]
     #[serial] // Run serially due to env modifications
-    fn new_ok() {
-        std::env::set_var("DOTENV_KEY", "dotenv://:testkey");
-        let vault = Vault::new();
-        assert!(vault.key.is_some());
-        assert!(vault.key.unwrap() == "dotenv://:testkey");
-        assert!(vault.path.is_some());
-        assert!(vault.path.unwrap() == env::current_dir().unwrap().join(".env.vault"));
-        std::env::remove_var("DOTENV_KEY");
+    #[cfg(feature = "keychain")]
+    fn keychain_enabled_from_env() {
+        env::set_var("DOTENV_VAULT_USE_KEYCHAIN", "1");
+        assert!(VaultConfig::default().keychain);
+
+        env::set_var("DOTENV_VAULT_USE_KEYCHAIN", "true");
+        assert!(!VaultConfig::default().keychain);
+
+        env::remove_var("DOTENV_VAULT_USE_KEYCHAIN");
+        assert!(!VaultConfig::default().keychain);
     }
 
     #[test]
-    fn instructions_ok() {
-        let vault = Vault::new();
-        let instructions = vault
-            .instructions("dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production");
+    #[serial] // Run serially due to env modifications
+    #[cfg(feature = "wincred")]
+    fn wincred_enabled_from_env() {
+        env::set_var("DOTENV_VAULT_USE_WINCRED", "1");
+        assert!(VaultConfig::default().wincred);
 
-        assert!(instructions.is_ok());
-        let (key, environment) = instructions.unwrap();
-        assert_eq!(key, "key_1234");
-        assert_eq!(environment, "DOTENV_VAULT_PRODUCTION");
+        env::remove_var("DOTENV_VAULT_USE_WINCRED");
+        assert!(!VaultConfig::default().wincred);
     }
 
     #[test]
-    fn instructions_invalid_scheme() {
-        let vault = Vault::new();
-        let instructions =
-            vault.instructions("invalid://dotenv.org/vault/.env.vault?environment=production");
+    #[serial] // Run serially due to env modifications
+    #[cfg(feature = "secret-service")]
+    fn secret_service_enabled_from_env() {
+        env::set_var("DOTENV_VAULT_USE_SECRET_SERVICE", "1");
+        assert!(VaultConfig::default().secret_service);
 
-        assert!(instructions.is_err());
-        assert!(matches!(instructions.unwrap_err(), Error::InvalidScheme));
+        env::remove_var("DOTENV_VAULT_USE_SECRET_SERVICE");
+        assert!(!VaultConfig::default().secret_service);
     }
 
     #[test]
-    fn instructions_missing_key() {
-        let vault = Vault::new();
-        let instructions =
-            vault.instructions("dotenv://dotenv.org/vault/.env.vault?environment=production");
+    #[serial] // Run serially due to env modifications
+    #[cfg(target_os = "linux")]
+    fn xdg_vault_path_from_config_home() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config_home = env::var_os("XDG_CONFIG_HOME");
+        env::set_var("XDG_CONFIG_HOME", tmp.path());
 
-        assert!(instructions.is_err());
-        assert!(matches!(instructions.unwrap_err(), Error::MissingKey));
+        let path = Vault::xdg_vault_path();
+
+        match config_home {
+            Some(config_home) => env::set_var("XDG_CONFIG_HOME", config_home),
+            None => env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        assert_eq!(
+            path,
+            Some(tmp.path().join("dotenv-vault").join(".env.vault"))
+        );
+
+        tmp.close().unwrap();
     }
 
     #[test]
-    fn instructions_missing_environment() {
-        let vault = Vault::new();
-        let instructions = vault.instructions("dotenv://:key_1234@dotenv.org/vault/.env.vault");
+    #[serial] // Run serially due to env modifications
+    #[cfg(target_os = "linux")]
+    fn to_map_xdg_fallback() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("project");
+        let config_dir = tmp.path().join("config").join("dotenv-vault");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(&config_dir).unwrap();
+        write_vault(
+            &config_dir.join(".env.vault"),
+            "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+        );
 
-        assert!(instructions.is_err());
-        assert!(matches!(
-            instructions.unwrap_err(),
-            Error::MissingEnvironment
-        ));
+        let cwd = env::current_dir().unwrap();
+        let config_home = env::var_os("XDG_CONFIG_HOME");
+        env::set_current_dir(&project).unwrap();
+        env::set_var("XDG_CONFIG_HOME", tmp.path().join("config"));
+        env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+
+        let config = VaultConfig {
+            xdg_fallback: true,
+            ..VaultConfig::default()
+        };
+        let fallback = Vault::new_with_config(config).to_map();
+        env::set_var("DOTENV_VAULT_XDG", "1");
+        let from_env = Vault::new().to_map();
+        env::remove_var("DOTENV_VAULT_XDG");
+        let missing = Vault::new().to_map();
+
+        env::remove_var("DOTENV_KEY");
+        match config_home {
+            Some(config_home) => env::set_var("XDG_CONFIG_HOME", config_home),
+            None => env::remove_var("XDG_CONFIG_HOME"),
+        }
+        env::set_current_dir(cwd).unwrap();
+
+        let fallback = fallback.unwrap();
+        assert_eq!(fallback["ALPHA"], "omega");
+        assert_eq!(fallback["BETA"], "gamma");
+        assert_eq!(from_env.unwrap(), fallback);
+        assert!(missing.is_err());
+
+        tmp.close().unwrap();
     }
 
     #[test]
-    fn decrypt_ok() {
-        let vault = Vault::new();
-        let decrypted = vault.decrypt(
-            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R".into(),
-            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
+    #[serial] // Run serially due to env modifications
+    fn new_with_config_docker_secret_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("DOTENV_KEY"),
+            "dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=production\n",
+        )
+        .unwrap();
+
+        let config = VaultConfig {
+            docker_secrets_dir: Some(tmp.path().to_path_buf()),
+            ..VaultConfig::default()
+        };
+        let from_secret = Vault::new_with_config(config.clone());
+        env::set_var(
+            "DOTENV_KEY",
+            "dotenv://:key_5678@dotenv.local/vault/.env.vault?environment=production",
         );
-        assert!(decrypted.is_ok());
+        let from_env = Vault::new_with_config(config);
+        env::remove_var("DOTENV_KEY");
+
         assert_eq!(
-            decrypted.unwrap(),
-            "# development@v6\nALPHA=\"zeta\"".as_bytes()
+            from_secret.key.as_deref(),
+            Some("dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=production")
+        );
+        assert_eq!(
+            from_env.key.as_deref(),
+            Some("dotenv://:key_5678@dotenv.local/vault/.env.vault?environment=production")
         );
+
+        tmp.close().unwrap();
     }
 
     #[test]
-    fn decrypt_invalid_key() {
-        let vault = Vault::new();
-        let decrypted = vault.decrypt(
-            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R".into(),
-            "01b08fe1173b781cce5fd1a18178c5cacdf3bb0845a8aa1b8089ac0751f7ed9c".into(),
-        );
-        assert!(matches!(decrypted, Err(Error::DecryptError(_))));
+    #[serial] // Run serially due to env modifications
+    fn new_with_config_docker_secret_key_missing() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        let config = VaultConfig {
+            docker_secrets_dir: Some(tmp.path().to_path_buf()),
+            ..VaultConfig::default()
+        };
+        let vault = Vault::new_with_config(config);
+
+        assert_eq!(vault.key, None);
+
+        tmp.close().unwrap();
     }
 
     #[test]
-    fn decrypt_invalid_ciphertext() {
-        let vault = Vault::new();
-        let decrypted = vault.decrypt(
-            "bQ4c611kJ7kVoUNzHXEbV+bTYc/4UVeyKXXgUpyaaIiUrzOrCauLix6lxrBm4FrCql6kxBA7f/oVO5U+kLMzHA==".into(),
-            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
-        );
-        assert!(matches!(decrypted, Err(Error::DecryptError(_))));
+    fn docker_secrets_dir_default() {
+        let disabled = VaultConfig::default();
+        let enabled = VaultConfig {
+            docker_secrets_enabled: true,
+            ..VaultConfig::default()
+        };
+
+        assert_eq!(disabled.docker_secrets_dir(), None);
+        if cfg!(target_os = "linux") {
+            assert_eq!(
+                enabled.docker_secrets_dir(),
+                Some(PathBuf::from("/run/secrets"))
+            );
+        } else {
+            assert_eq!(enabled.docker_secrets_dir(), None);
+        }
     }
 
     #[test]
-    fn decrypt_short_key() {
-        let vault = Vault::new();
-        let decrypted = vault.decrypt(
-            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R".into(),
-            "caa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
-        );
-        assert!(matches!(decrypted, Err(Error::InvalidKey)));
+    fn to_map_key_order() {
+        let contents = "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"\nDOTENV_VAULT_STAGING=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"\nDOTENV_VAULT_DEVELOPMENT=\"9gAS9K5KNxQG6o8Jx2m9OMPro7ieKf8S2W5Ww0LW0iYQfyr06FzEpeeobdOHR8wYY+OpP0LIdQRObLWyFOU3a9rmiSVVBD14elG8bH2MsAYTii7OHuQQqorTUiMK6ZCH/dk=\"";
+        let keys = [
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production&timestamp=1700000000",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=staging&timestamp=1700000300",
+            "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development&timestamp=1700000200",
+        ]
+        .join(",");
+        let vault = Vault::from_bytes(contents, &keys).with_logging_disabled();
+
+        let document = vault.clone().to_map().unwrap();
+        let reverse = vault
+            .clone()
+            .with_key_order(KeyOrder::ReverseDocumentOrder)
+            .to_map()
+            .unwrap();
+        let most_recent = vault
+            .with_key_order(KeyOrder::MostRecentFirst)
+            .to_map()
+            .unwrap();
+
+        assert_eq!(document["ALPHA"], "omega");
+        assert_eq!(reverse["PORT"], "8080");
+        assert_eq!(most_recent["ALPHA"], "zeta");
     }
 
     #[test]
-    fn decrypt_invalid_hex() {
-        let vault = Vault::new();
-        let decrypted = vault.decrypt(
-            "s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R".into(),
-            "XXcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
+    fn ordered_keys_without_version() {
+        let vault = Vault::default().with_key_order(KeyOrder::MostRecentFirst);
+        let keys = vault.ordered_keys(
+            "dotenv://:key_a@dotenv.local/vault/.env.vault?environment=production,dotenv://:key_b@dotenv.local/vault/.env.vault?environment=production&timestamp=2,dotenv://:key_c@dotenv.local/vault/.env.vault?environment=production",
+        );
+
+        assert_eq!(
+            keys,
+            [
+                "dotenv://:key_b@dotenv.local/vault/.env.vault?environment=production&timestamp=2",
+                "dotenv://:key_a@dotenv.local/vault/.env.vault?environment=production",
+                "dotenv://:key_c@dotenv.local/vault/.env.vault?environment=production",
+            ]
         );
-        assert!(matches!(decrypted, Err(Error::HexError(_))));
     }
 
     #[test]
-    fn decrypt_invalid_base64() {
-        let vault = Vault::new();
-        let decrypted = vault.decrypt(
-            "FFFFFFFs7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R"
-                .into(),
-            "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00".into(),
-        );
-        assert!(matches!(decrypted, Err(Error::DecodeError(_))));
+    #[serial] // Run serially due to env modifications
+    fn load_per_environment_file_fallback_to_env() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut env_file = File::create(tmp.path().join(".env")).unwrap();
+        env_file
+            .write_all("ALPHA=\"from .env\"".as_bytes())
+            .unwrap();
+        env_file.sync_all().unwrap();
+
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&tmp).unwrap();
+
+        let vault = Vault::from_key("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let map = vault.to_map();
+
+        assert!(map.is_ok());
+        assert_eq!(map.unwrap().get("ALPHA").unwrap(), "from .env");
+
+        env::set_current_dir(cwd).unwrap();
+        tmp.close().unwrap();
     }
 
     #[test]
-    fn parse_ok() {
+    fn diff_ok() {
         let tmp = tempfile::tempdir().unwrap();
         let vault_path = tmp.path().join(".env.vault");
         let mut vault = File::create(&vault_path).unwrap();
         vault
-            .write_all("DOTENV_VAULT_DEVELOPMENT=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .write_all("DOTENV_VAULT_PRODUCTION=\"IEbPKaWgX5s348n4moWZmBfpBYsXK560P70OaWFIHG9jmfvHfpxe0zfwVJOHShJop07JP5or+68OJ7D6mIXtvDnE\"\nDOTENV_VAULT_STAGING=\"kMQuGPLeVAnBcQ+0rC9IwjfoBrcrUktuRxoe8VHdPwjtAT4xQ82WWvApTXFCtKfRAbiuSfxu/PArxx5qRdsIDnN8/rU=\"".as_bytes())
             .unwrap();
         vault.sync_all().unwrap();
 
-        let vault = Vault {
-            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development".into()),
-            path: Some(vault_path)
-        };
-        let parsed = vault.parse();
+        let production = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let staging = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=staging");
+        let diff = production.diff(&staging);
 
-        assert!(parsed.is_ok());
+        assert!(diff.is_ok());
         assert_eq!(
-            parsed.unwrap(),
+            diff.unwrap(),
             vec![
-                35, 32, 100, 101, 118, 101, 108, 111, 112, 109, 101, 110, 116, 64, 118, 54, 10, 65,
-                76, 80, 72, 65, 61, 34, 122, 101, 116, 97, 34
+                VaultDiff {
+                    key: "ALPHA".into(),
+                    left: Some("zeta".into()),
+                    right: Some("omega".into()),
+                },
+                VaultDiff {
+                    key: "LEFT".into(),
+                    left: Some("only".into()),
+                    right: None,
+                },
+                VaultDiff {
+                    key: "RIGHT".into(),
+                    left: None,
+                    right: Some("only".into()),
+                },
             ]
         );
 
@@ -335,95 +5407,90 @@ mod tests {
     }
 
     #[test]
-    fn parse_invalid_environment() {
+    fn verify_ok() {
         let tmp = tempfile::tempdir().unwrap();
-        let vault_path = tmp.path().join(".env.vault");
-        let mut vault = File::create(&vault_path).unwrap();
-        vault
-            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
-            .unwrap();
-        vault.sync_all().unwrap();
+        write_vault(
+            &tmp.path().join(".env.vault"),
+            "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==",
+        );
 
-        let vault = Vault {
-            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development".into()),
-            path: Some(vault_path)
-        };
-        let parsed = vault.parse();
+        let vault = Vault::from_path(tmp.path().join(".env.vault"), "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let verified = vault.verify();
 
-        assert!(parsed.is_err());
-        assert!(matches!(parsed.unwrap_err(), Error::InvalidKey));
+        assert!(verified.is_ok());
+        assert_eq!(verified.unwrap(), ("production".to_string(), 2));
 
         tmp.close().unwrap();
     }
 
     #[test]
-    fn parse_invalid_key() {
+    fn verify_missing_vault() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        let vault = Vault::from_path(tmp.path().join(".env.vault"), "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let verified = vault.verify();
+
+        assert!(matches!(verified, Err(Error::VaultNotFound)));
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn parse_multiple_environments() {
         let tmp = tempfile::tempdir().unwrap();
         let vault_path = tmp.path().join(".env.vault");
         let mut vault = File::create(&vault_path).unwrap();
         vault
-            .write_all("DOTENV_VAULT_PRODUCTION=\"XXNYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+            .write_all("DOTENV_VAULT_DEVELOPMENT=\"XXNYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"\nDOTENV_VAULT_STAGING=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
             .unwrap();
         vault.sync_all().unwrap();
 
-        let vault = Vault {
-            key: Some("dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development".into()),
-            path: Some(vault_path)
-        };
-        let parsed = vault.parse();
+        // production is missing and development cannot be decrypted, so staging is used
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production&environment=development&environment=staging");
+        let decrypted = vault.decrypt_entry();
 
-        assert!(parsed.is_err());
-        assert!(matches!(parsed.unwrap_err(), Error::InvalidKey));
+        assert!(decrypted.is_ok());
+        let (entry, plaintext) = decrypted.unwrap();
+        assert_eq!(entry, "DOTENV_VAULT_STAGING");
+        assert_eq!(plaintext, "# development@v6\nALPHA=\"zeta\"".as_bytes());
 
         tmp.close().unwrap();
     }
 
     #[test]
-    fn parse_multiple_keys() {
+    #[serial] // Run serially due to env modifications
+    fn load_into_ok() {
         let tmp = tempfile::tempdir().unwrap();
         let vault_path = tmp.path().join(".env.vault");
-        let mut vault = File::create(&vault_path).unwrap();
-        vault
-            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
-            .unwrap();
-        vault.sync_all().unwrap();
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
 
-        let vault = Vault {
-            key: Some("dotenv://:key_XXcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development,dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
-            path: Some(vault_path)
-        };
-        let parsed = vault.parse();
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let mut map = HashMap::from([("ALPHA".to_string(), "existing".to_string())]);
+        let result = vault.load_into(&mut map);
 
-        assert!(parsed.is_ok());
-        assert_eq!(
-            parsed.unwrap(),
-            vec![
-                35, 32, 100, 101, 118, 101, 108, 111, 112, 109, 101, 110, 116, 64, 118, 54, 10, 65,
-                76, 80, 72, 65, 61, 34, 122, 101, 116, 97, 34
-            ]
-        );
+        assert!(result.is_ok());
+        assert_eq!(map.get("ALPHA").map(String::as_str), Some("existing"));
+        assert_eq!(map.get("BETA").map(String::as_str), Some("gamma"));
+        assert!(std::env::var("BETA").is_err());
 
         tmp.close().unwrap();
     }
 
     #[test]
-    fn parse_multiple_invalid_keys() {
+    #[serial] // Run serially due to env modifications
+    fn load_override_into_ok() {
         let tmp = tempfile::tempdir().unwrap();
         let vault_path = tmp.path().join(".env.vault");
-        let mut vault = File::create(&vault_path).unwrap();
-        vault
-            .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
-            .unwrap();
-        vault.sync_all().unwrap();
+        write_vault(&vault_path, "nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==");
 
-        let vault = Vault {
-            key: Some("dotenv://:key_XXcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=development,dotenv://:key_XXYY6504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production".into()),
-            path: Some(vault_path)
-        };
-        let parsed = vault.parse();
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        let mut map = HashMap::from([("ALPHA".to_string(), "existing".to_string())]);
+        let result = vault.load_override_into(&mut map);
 
-        assert!(parsed.is_err());
-        assert!(matches!(parsed.unwrap_err(), Error::InvalidKey));
+        assert!(result.is_ok());
+        assert_eq!(map.get("ALPHA").map(String::as_str), Some("omega"));
+        assert_eq!(map.get("BETA").map(String::as_str), Some("gamma"));
+        assert!(std::env::var("BETA").is_err());
 
         tmp.close().unwrap();
     }
@@ -0,0 +1,79 @@
+use super::log::LogLevel;
+use super::vault::Vault;
+use super::vault_config::{KeyOrder, VaultConfig};
+
+use std::path::PathBuf;
+
+/// Builds a [`Vault`] step by step, falling back to the same defaults as [`Vault::new`]
+///
+/// # Examples
+/// ```no_run
+/// use dotenv_vault::VaultBuilder;
+///
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     VaultBuilder::new()
+///         .with_path("config/.env.vault")
+///         .with_logging_disabled()
+///         .build()
+///         .load()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct VaultBuilder {
+    key: Option<String>,
+    path: Option<PathBuf>,
+    config: VaultConfig,
+}
+
+impl VaultBuilder {
+    /// Create a new builder using the *DOTENV_KEY* environment variable, a *.env.vault* file in
+    /// the current directory and the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the given dotenv key instead of the *DOTENV_KEY* environment variable
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    /// Use the given *.env.vault* file instead of the one in the current directory
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Use the given options
+    pub fn with_config(mut self, config: VaultConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Skip all log output while locating and loading the *.env.vault* file, regardless of
+    /// *DOTENV_VAULT_LOG_LEVEL*
+    pub fn with_logging_disabled(mut self) -> Self {
+        self.config.log_level = LogLevel::None;
+        self
+    }
+
+    /// Try the comma-separated keys of the dotenv key in the given order, instead of
+    /// [`KeyOrder::DocumentOrder`]
+    pub fn with_key_order(mut self, order: KeyOrder) -> Self {
+        self.config.key_order = order;
+        self
+    }
+
+    /// Read the dotenv key from the environment variable `name` instead of *DOTENV_KEY*, e.g.
+    /// `APP_DOTENV_KEY`
+    pub fn with_env_var_name(mut self, name: &str) -> Self {
+        self.config.key_var_name = name.to_string();
+        self
+    }
+
+    /// Create the [`Vault`]
+    pub fn build(self) -> Vault {
+        Vault::from_parts(self.key.as_deref(), self.path, self.config)
+    }
+}
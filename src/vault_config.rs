@@ -0,0 +1,190 @@
+use super::log::LogLevel;
+
+use std::{env, path::PathBuf};
+
+/// Options controlling how a [`Vault`](crate::Vault) locates and loads its *.env.vault* file
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct VaultConfig {
+    /// Look for a *.env.{environment}.vault* file before the *.env.vault* file, where the
+    /// environment is taken from *DOTENV_ENVIRONMENT* or the `environment` parameter of the key
+    pub use_per_environment_files: bool,
+    /// Order in which the comma-separated keys of a dotenv key are tried
+    pub key_order: KeyOrder,
+    /// Name of the environment variable the dotenv key is read from, e.g. `APP_DOTENV_KEY`,
+    /// *DOTENV_KEY* by default
+    pub key_var_name: String,
+    /// Prefix of the entries in the *.env.vault* file, followed by the uppercase environment
+    /// name, e.g. `DOTENV_VAULT_` for `DOTENV_VAULT_PRODUCTION`
+    pub vault_key_prefix: String,
+    /// Suffix of the entries in the *.env.vault* file, following the uppercase environment name,
+    /// e.g. `_APP` for `DOTENV_VAULT_PRODUCTION_APP`, none by default
+    pub vault_key_suffix: Option<String>,
+    /// If there is no *.env.vault* file in the current directory, look for one in its ancestors
+    /// up to the repository root containing *.git*, as found by
+    /// [`Vault::find_vault_in_ancestors`](crate::Vault::find_vault_in_ancestors)
+    pub git_root_discovery: bool,
+    /// If no *.env.vault* file is found otherwise, fall back to the user-level one at
+    /// [`Vault::xdg_vault_path`](crate::Vault::xdg_vault_path), enabled by
+    /// *DOTENV_VAULT_XDG=1* by default
+    pub xdg_fallback: bool,
+    /// Read the dotenv key of the current directory from the macOS Keychain when the
+    /// *DOTENV_KEY* environment variable is not set, see
+    /// [`Vault::save_key_to_keychain`](crate::Vault::save_key_to_keychain), enabled by
+    /// *DOTENV_VAULT_USE_KEYCHAIN=1* by default
+    #[cfg(feature = "keychain")]
+    pub keychain: bool,
+    /// Read the dotenv key of the current directory from the Windows Credential Manager when the
+    /// *DOTENV_KEY* environment variable is not set, see
+    /// [`Vault::save_key_to_wincred`](crate::Vault::save_key_to_wincred), enabled by
+    /// *DOTENV_VAULT_USE_WINCRED=1* by default
+    #[cfg(feature = "wincred")]
+    pub wincred: bool,
+    /// Read the dotenv key of the current directory from the Secret Service, such as GNOME
+    /// Keyring or KWallet, when the *DOTENV_KEY* environment variable is not set, see
+    /// [`Vault::save_key_to_secret_service`](crate::Vault::save_key_to_secret_service), enabled
+    /// by *DOTENV_VAULT_USE_SECRET_SERVICE=1* by default
+    #[cfg(feature = "secret-service")]
+    pub secret_service: bool,
+    /// Read the dotenv key from the *DOTENV_KEY* file in `docker_secrets_dir` when the
+    /// *DOTENV_KEY* environment variable is not set
+    pub docker_secrets_enabled: bool,
+    /// Directory of mounted Docker or Kubernetes secrets, checked for a *DOTENV_KEY* file even if
+    /// `docker_secrets_enabled` is `false`, defaulting to */run/secrets* on Linux
+    pub docker_secrets_dir: Option<PathBuf>,
+    /// Expand `${VAR}` and `$VAR` references in the decrypted contents before loading them,
+    /// resolving to the environment first and then to any variable in the vault, and failing
+    /// on circular references
+    pub interpolate: bool,
+    /// Strip one layer of matching `"` or `'` quotes from each value after parsing, for vaults
+    /// whose values are quoted twice such as `VAR="\"value\""`
+    pub strip_outer_quotes: bool,
+    /// Fail with [`Error::NonceReuse`](crate::Error::NonceReuse) instead of decrypting when two
+    /// entries of the *.env.vault* file share a nonce, checked with
+    /// [`Vault::validate_nonce_uniqueness`](crate::Vault::validate_nonce_uniqueness)
+    pub detect_nonce_reuse: bool,
+    /// How much to log to stderr while locating the *.env.vault* file, read from
+    /// *DOTENV_VAULT_LOG_LEVEL* by default
+    pub log_level: LogLevel,
+    /// Record every [`Vault::load`](crate::Vault::load) and [`Vault::to_map`](crate::Vault::to_map)
+    /// in the log returned by [`Vault::access_log`](crate::Vault::access_log)
+    pub audit: bool,
+    /// Fetch the *.env.vault* file from this URL instead of reading it from disk
+    #[cfg(feature = "remote")]
+    pub vault_url: Option<String>,
+    /// Bearer token sent when fetching `vault_url`
+    #[cfg(feature = "remote")]
+    pub vault_auth_token: Option<String>,
+    /// S3 bucket holding the *.env.vault* file, used together with `s3_key`
+    #[cfg(feature = "s3")]
+    pub s3_bucket: Option<String>,
+    /// Key of the *.env.vault* object in `s3_bucket`
+    #[cfg(feature = "s3")]
+    pub s3_key: Option<String>,
+    /// Address of the HashiCorp Vault server to fetch the dotenv key from, used together with
+    /// `hc_vault_token` and `hc_vault_path`
+    #[cfg(feature = "hashicorp-vault")]
+    pub hc_vault_addr: Option<String>,
+    /// Token used to authenticate with HashiCorp Vault
+    #[cfg(feature = "hashicorp-vault")]
+    pub hc_vault_token: Option<String>,
+    /// API path of the KV v2 secret holding the dotenv key in its `DOTENV_KEY` field, e.g.
+    /// `secret/data/my-app`
+    #[cfg(feature = "hashicorp-vault")]
+    pub hc_vault_path: Option<String>,
+    /// Name or ARN of an AWS Secrets Manager secret to fetch, used as described by
+    /// `aws_secret_mode`
+    #[cfg(feature = "aws-secrets")]
+    pub aws_secret_name: Option<String>,
+    /// What the value of `aws_secret_name` holds
+    #[cfg(feature = "aws-secrets")]
+    pub aws_secret_mode: AwsSecretMode,
+}
+
+/// What the AWS Secrets Manager secret named by
+/// [`VaultConfig::aws_secret_name`] holds
+#[cfg(feature = "aws-secrets")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AwsSecretMode {
+    /// The dotenv key, used to decrypt the local *.env.vault* file
+    #[default]
+    Key,
+    /// The contents of the *.env.vault* file, decrypted with the usual dotenv key
+    Vault,
+}
+
+/// Order in which the comma-separated keys of a dotenv key are tried, stopping at the first one
+/// that decrypts the vault
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// The order the keys are listed in
+    #[default]
+    DocumentOrder,
+    /// The reverse of the order the keys are listed in
+    ReverseDocumentOrder,
+    /// Descending by the timestamp in the `timestamp` parameter of each key, such as
+    /// `dotenv://:key_1234@dotenv.org/vault/.env.vault?environment=production&timestamp=1700000000`,
+    /// with keys without a timestamp last in the order they are listed in
+    MostRecentFirst,
+}
+
+impl VaultConfig {
+    /// The Docker secrets directory to read the dotenv key from, if any
+    pub(crate) fn docker_secrets_dir(&self) -> Option<PathBuf> {
+        if self.docker_secrets_dir.is_some() {
+            return self.docker_secrets_dir.clone();
+        }
+
+        if self.docker_secrets_enabled && cfg!(target_os = "linux") {
+            return Some(PathBuf::from("/run/secrets"));
+        }
+
+        None
+    }
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            use_per_environment_files: true,
+            key_order: KeyOrder::default(),
+            key_var_name: "DOTENV_KEY".to_string(),
+            vault_key_prefix: "DOTENV_VAULT_".to_string(),
+            vault_key_suffix: None,
+            git_root_discovery: false,
+            xdg_fallback: env::var("DOTENV_VAULT_XDG").is_ok_and(|xdg| xdg == "1"),
+            #[cfg(feature = "keychain")]
+            keychain: env::var("DOTENV_VAULT_USE_KEYCHAIN").is_ok_and(|keychain| keychain == "1"),
+            #[cfg(feature = "wincred")]
+            wincred: env::var("DOTENV_VAULT_USE_WINCRED").is_ok_and(|wincred| wincred == "1"),
+            #[cfg(feature = "secret-service")]
+            secret_service: env::var("DOTENV_VAULT_USE_SECRET_SERVICE")
+                .is_ok_and(|secret_service| secret_service == "1"),
+            docker_secrets_enabled: false,
+            docker_secrets_dir: None,
+            interpolate: false,
+            strip_outer_quotes: false,
+            detect_nonce_reuse: false,
+            log_level: LogLevel::from_env(),
+            audit: false,
+            #[cfg(feature = "remote")]
+            vault_url: None,
+            #[cfg(feature = "remote")]
+            vault_auth_token: None,
+            #[cfg(feature = "s3")]
+            s3_bucket: None,
+            #[cfg(feature = "s3")]
+            s3_key: None,
+            #[cfg(feature = "hashicorp-vault")]
+            hc_vault_addr: None,
+            #[cfg(feature = "hashicorp-vault")]
+            hc_vault_token: None,
+            #[cfg(feature = "hashicorp-vault")]
+            hc_vault_path: None,
+            #[cfg(feature = "aws-secrets")]
+            aws_secret_name: None,
+            #[cfg(feature = "aws-secrets")]
+            aws_secret_mode: AwsSecretMode::default(),
+        }
+    }
+}
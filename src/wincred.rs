@@ -0,0 +1,75 @@
+use super::errors::{Error, Result};
+
+use std::{env, ptr, slice};
+use windows::{
+    core::{PCWSTR, PWSTR},
+    Win32::{
+        Foundation::ERROR_NOT_FOUND,
+        Security::Credentials::{
+            CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE,
+            CRED_TYPE_GENERIC,
+        },
+    },
+};
+
+/// Target name of the credential for the current directory, as a null-terminated wide string
+fn target_name() -> Result<Vec<u16>> {
+    let target = format!("dotenv-vault/{}", env::current_dir()?.display());
+    Ok(target.encode_utf16().chain(Some(0)).collect())
+}
+
+/// Read the dotenv key of the current directory from the Windows Credential Manager
+///
+/// Returns `None` if there is no credential for the current directory.
+pub(crate) fn read_key() -> Result<Option<String>> {
+    let target = target_name()?;
+    let mut credential: *mut CREDENTIALW = ptr::null_mut();
+
+    // SAFETY: `target` is null-terminated and outlives the call
+    let read = unsafe {
+        CredReadW(
+            PCWSTR(target.as_ptr()),
+            CRED_TYPE_GENERIC,
+            0,
+            &mut credential,
+        )
+    };
+    match read {
+        Ok(()) => {}
+        Err(err) if err.code() == ERROR_NOT_FOUND.to_hresult() => return Ok(None),
+        Err(err) => return Err(Error::WincredError(err)),
+    }
+
+    // SAFETY: `CredReadW` succeeded, so `credential` points to a credential whose blob holds
+    // `CredentialBlobSize` bytes, and it is freed exactly once after the blob is copied
+    let key = unsafe {
+        let blob = match (*credential).CredentialBlobSize {
+            0 => &[][..],
+            size => slice::from_raw_parts((*credential).CredentialBlob, size as usize),
+        };
+        let key = String::from_utf8_lossy(blob).trim().to_string();
+        CredFree(credential as *const _);
+        key
+    };
+
+    Ok(Some(key).filter(|key| !key.is_empty()))
+}
+
+/// Store the dotenv key of the current directory in the Windows Credential Manager, replacing
+/// any existing one
+pub(crate) fn save_key(key: &str) -> Result<()> {
+    let mut target = target_name()?;
+    let mut blob = key.trim().as_bytes().to_vec();
+
+    let credential = CREDENTIALW {
+        Type: CRED_TYPE_GENERIC,
+        TargetName: PWSTR(target.as_mut_ptr()),
+        CredentialBlobSize: blob.len() as u32,
+        CredentialBlob: blob.as_mut_ptr(),
+        Persist: CRED_PERSIST_LOCAL_MACHINE,
+        ..Default::default()
+    };
+
+    // SAFETY: the target name and blob the credential points to outlive the call
+    unsafe { CredWriteW(&credential, 0) }.map_err(Error::WincredError)
+}
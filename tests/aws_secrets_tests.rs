@@ -0,0 +1,134 @@
+#![cfg(feature = "aws-secrets")]
+
+use dotenv_vault::{AwsSecretMode, VaultBuilder, VaultConfig};
+use serial_test::serial;
+use std::{env, fs::File, io::prelude::*};
+use tempfile::tempdir;
+
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+const VAULT: &str = "DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"";
+
+fn set_aws_env(endpoint: &str) {
+    env::set_var("AWS_ENDPOINT_URL", endpoint);
+    env::set_var("AWS_REGION", "us-east-1");
+    env::set_var("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE");
+    env::set_var(
+        "AWS_SECRET_ACCESS_KEY",
+        "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+    );
+    env::set_var("AWS_EC2_METADATA_DISABLED", "true");
+}
+
+fn clear_aws_env() {
+    for name in [
+        "AWS_ENDPOINT_URL",
+        "AWS_REGION",
+        "AWS_ACCESS_KEY_ID",
+        "AWS_SECRET_ACCESS_KEY",
+        "AWS_EC2_METADATA_DISABLED",
+    ] {
+        env::remove_var(name);
+    }
+}
+
+fn secret_response(value: &str) -> String {
+    serde_json::json!({
+        "ARN": "arn:aws:secretsmanager:us-east-1:123456789012:secret:my-app-a1b2c3",
+        "Name": "my-app",
+        "SecretString": value,
+    })
+    .to_string()
+}
+
+fn aws_config(mode: AwsSecretMode) -> VaultConfig {
+    let mut config = VaultConfig::default();
+    config.aws_secret_name = Some("my-app".to_string());
+    config.aws_secret_mode = mode;
+    config
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn aws_secret_key() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .match_header("x-amz-target", "secretsmanager.GetSecretValue")
+        .match_header(
+            "authorization",
+            mockito::Matcher::Regex("^AWS4-HMAC-SHA256 ".to_string()),
+        )
+        .match_body(mockito::Matcher::Json(
+            serde_json::json!({ "SecretId": "my-app" }),
+        ))
+        .with_header("content-type", "application/x-amz-json-1.1")
+        .with_body(secret_response(DOTENV_KEY))
+        .create();
+    set_aws_env(&server.url());
+
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(&vault_path).unwrap();
+    vault.write_all(VAULT.as_bytes()).unwrap();
+    vault.sync_all().unwrap();
+
+    let vault = VaultBuilder::new()
+        .with_config(aws_config(AwsSecretMode::Key))
+        .with_path(&vault_path)
+        .build();
+    let map = vault.to_map().unwrap();
+
+    mock.assert();
+    assert_eq!(map.get("ALPHA").map(String::as_str), Some("omega"));
+    assert_eq!(map.get("BETA").map(String::as_str), Some("gamma"));
+
+    clear_aws_env();
+    tmp.close().unwrap();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn aws_secret_vault() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .match_header("x-amz-target", "secretsmanager.GetSecretValue")
+        .with_header("content-type", "application/x-amz-json-1.1")
+        .with_body(secret_response(VAULT))
+        .create();
+    set_aws_env(&server.url());
+
+    let vault = VaultBuilder::new()
+        .with_config(aws_config(AwsSecretMode::Vault))
+        .with_key(DOTENV_KEY)
+        .build();
+    let map = vault.to_map().unwrap();
+
+    mock.assert();
+    assert_eq!(map.get("ALPHA").map(String::as_str), Some("omega"));
+    assert_eq!(map.get("BETA").map(String::as_str), Some("gamma"));
+
+    clear_aws_env();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn aws_secret_unavailable() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .with_status(400)
+        .with_body(r#"{"__type":"ResourceNotFoundException"}"#)
+        .create();
+    set_aws_env(&server.url());
+
+    let vault = VaultBuilder::new()
+        .with_config(aws_config(AwsSecretMode::Key))
+        .build();
+    let err = vault.to_map().unwrap_err();
+
+    mock.assert();
+    assert_eq!(err.error_code(), "AWS_SECRET_FETCH_FAILED");
+
+    clear_aws_env();
+}
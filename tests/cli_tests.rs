@@ -1,7 +1,64 @@
+#![cfg(feature = "cli")]
+
 use assert_cmd::Command;
-use std::{env, fs::File, io::prelude::*};
+use std::{collections::HashMap, env, fs::File, io::prelude::*, path::Path};
 use tempfile::tempdir;
 
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+/// Encrypts `ALPHA="zeta"`, `QUOTED='he said "hi" to $USER'` and `MULTI="line one\nline two"`
+const ESCAPES_CIPHERTEXT: &str = "urFzqN5UkXsh6nlSGgg0u5X0k/Qt2wCn+4QNr1r7hQEPUTcbxy/StjiGTVqopF3jZu2vIZmyW0zcML1uwjcjrb/T6YDHIvS7oCUSO7H9hLKH7nPq+i8vhw5RpY20fGL0wC0=";
+
+fn write_vault(dir: &Path, ciphertext: &str) {
+    let mut vault = File::create(dir.join(".env.vault")).unwrap();
+    vault
+        .write_all(format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext).as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+}
+
+const NEW_DOTENV_KEY: &str = "dotenv://:key_01b08fe1173b781cce5fd1a18178c5cacdf3bb0845a8aa1b8089ac0751f7ed9c@dotenv.local/vault/.env.vault?environment=production";
+
+const STAGING_DOTENV_KEY: &str = "dotenv://:key_01b08fe1173b781cce5fd1a18178c5cacdf3bb0845a8aa1b8089ac0751f7ed9c@dotenv.local/vault/.env.vault?environment=staging";
+
+/// Encrypts `ALPHA="from staging"` and `EXTRA="only staging"` with the staging key
+const STAGING_CIPHERTEXT: &str =
+    "u1ir+wuWtAmepyLx6DlYMs9a5sXunEiC+AP3sbFH6a53H5r2597eZP8U6maXleJ9w3pXcQEb2Kr2QFqIMu0MWNjTzohP";
+
+fn write_staging_vault(dir: &Path) {
+    std::fs::write(
+        dir.join(".env.vault"),
+        format!(
+            "DOTENV_VAULT_PRODUCTION=\"{}\"\nDOTENV_VAULT_STAGING=\"{}\"\n",
+            ESCAPES_CIPHERTEXT, STAGING_CIPHERTEXT
+        ),
+    )
+    .unwrap();
+}
+
+fn print_env(format: &str) -> String {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .current_dir(tmp.path())
+        .args(["run", "--print-env", "--format", format]);
+
+    cmd.assert().success();
+    let output = cmd.output().unwrap();
+    tmp.close().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn expected_env() -> HashMap<String, String> {
+    HashMap::from([
+        ("ALPHA".to_string(), "zeta".to_string()),
+        ("QUOTED".to_string(), "he said \"hi\" to $USER".to_string()),
+        ("MULTI".to_string(), "line one\nline two".to_string()),
+    ])
+}
+
 #[test]
 fn dotenv_vault_cli() {
     env::set_var("DOTENV_KEY", "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
@@ -71,3 +128,744 @@ fn dotenv_vault_cli() {
     env::remove_var("DOTENV_KEY");
     env::set_current_dir(cwd).unwrap();
 }
+
+#[test]
+#[cfg(unix)]
+fn dotenv_vault_cli_print_env_shell() {
+    let output = print_env("shell");
+
+    let script = format!(
+        "{}printf '%s|%s|%s' \"$ALPHA\" \"$QUOTED\" \"$MULTI\"",
+        output
+    );
+    let evaluated = std::process::Command::new("sh")
+        .args(["-c", &script])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(evaluated.stdout).unwrap(),
+        "zeta|he said \"hi\" to $USER|line one\nline two"
+    );
+}
+
+#[test]
+fn dotenv_vault_cli_print_env_json() {
+    let output = print_env("json");
+
+    let parsed: HashMap<String, String> = serde_json::from_str(&output).unwrap();
+    assert_eq!(parsed, expected_env());
+}
+
+#[test]
+fn dotenv_vault_cli_print_env_json_only_loaded_vars() {
+    let tmp = tempdir().unwrap();
+    File::create(tmp.path().join(".env"))
+        .unwrap()
+        .write_all(b"FALLBACK=\"from .env\"")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env_remove("DOTENV_KEY")
+        .env("AMBIENT_ONLY", "from the process")
+        .current_dir(tmp.path())
+        .args(["run", "--print-env", "--format", "json"]);
+
+    cmd.assert().success();
+    let output = String::from_utf8(cmd.output().unwrap().stdout).unwrap();
+    assert_eq!(output, "{\n  \"FALLBACK\": \"from .env\"\n}\n");
+
+    let parsed: HashMap<String, String> = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        parsed,
+        HashMap::from([("FALLBACK".to_string(), "from .env".to_string())])
+    );
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_print_env_toml() {
+    let output = print_env("toml");
+
+    let parsed: HashMap<String, HashMap<String, String>> = toml::from_str(&output).unwrap();
+    assert_eq!(parsed["env"], expected_env());
+}
+
+#[test]
+fn dotenv_vault_cli_print_env_dotenv() {
+    let output = print_env("dotenv");
+
+    let parsed = dotenvy::from_read_iter(output.as_bytes())
+        .collect::<Result<HashMap<String, String>, _>>()
+        .unwrap();
+    assert_eq!(parsed, expected_env());
+}
+
+#[test]
+fn dotenv_vault_cli_print_env_invalid_format() {
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.args(["run", "--print-env", "--format", "yaml"]);
+    cmd.assert().failure();
+}
+
+#[test]
+fn dotenv_vault_cli_output() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+    let output_path = tmp.path().join("decrypted.env");
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .env_remove("ALPHA")
+        .current_dir(tmp.path())
+        .arg("run")
+        .arg("--output")
+        .arg(&output_path);
+    if cfg!(windows) {
+        cmd.args(["--", "cmd", "/C", "echo %ALPHA%"]);
+    } else {
+        cmd.args(["--", "sh", "-c", "printenv ALPHA"]);
+    }
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim_end(), "zeta");
+
+    let contents = std::fs::read_to_string(&output_path).unwrap();
+    assert_eq!(
+        contents,
+        "ALPHA=\"zeta\"\nMULTI=\"line one\\nline two\"\nQUOTED=\"he said \\\"hi\\\" to \\$USER\"\n"
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(&output_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_output_with_print_env() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+    let output_path = tmp.path().join("decrypted.env");
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .current_dir(tmp.path())
+        .args(["run", "--print-env", "--output", "decrypted.env"]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let parsed = dotenvy::from_path_iter(&output_path)
+        .unwrap()
+        .collect::<Result<HashMap<String, String>, _>>()
+        .unwrap();
+    assert_eq!(parsed, expected_env());
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_validate() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .current_dir(tmp.path())
+        .arg("validate");
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Decrypted 3 variables from the production environment\n"
+    );
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_validate_env_file_with_keys_file() {
+    let tmp = tempdir().unwrap();
+    let vault_dir = tmp.path().join("config");
+    std::fs::create_dir(&vault_dir).unwrap();
+    write_vault(&vault_dir, ESCAPES_CIPHERTEXT);
+    std::fs::write(
+        tmp.path().join(".env.keys"),
+        format!("DOTENV_KEY_PRODUCTION=\"{}\"", DOTENV_KEY),
+    )
+    .unwrap();
+
+    // The key comes from the .env.keys file, the vault from --env-file
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env_remove("DOTENV_KEY")
+        .env("DOTENV_ENVIRONMENT", "production")
+        .current_dir(tmp.path())
+        .args(["validate", "--env-file", "config/.env.vault"]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Decrypted 3 variables from the production environment\n"
+    );
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_validate_key_mismatch() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.arg("validate")
+        .arg("--env-file")
+        .arg(tmp.path().join(".env.vault"))
+        .args(["--dotenv-key", "dotenv://:key_01b08fe1173b781cce5fd1a18178c5cacdf3bb0845a8aa1b8089ac0751f7ed9c@dotenv.local/vault/.env.vault?environment=production"]);
+
+    let output = cmd.output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("INVALID_DOTENV_KEY"));
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_rotate() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path()).args([
+        "rotate",
+        "--old-key",
+        DOTENV_KEY,
+        "--new-key",
+        NEW_DOTENV_KEY,
+    ]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let rotated = String::from_utf8(output.stdout).unwrap();
+    assert!(rotated.starts_with("DOTENV_VAULT_PRODUCTION=\""));
+    assert!(!rotated.contains(ESCAPES_CIPHERTEXT));
+
+    // Without --in-place the vault file is left untouched
+    let contents = std::fs::read_to_string(tmp.path().join(".env.vault")).unwrap();
+    assert!(contents.contains(ESCAPES_CIPHERTEXT));
+
+    let vault = dotenv_vault::Vault::from_bytes(rotated, NEW_DOTENV_KEY);
+    assert_eq!(vault.to_map().unwrap(), expected_env());
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_rotate_in_place() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path()).args([
+        "rotate",
+        "--old-key",
+        DOTENV_KEY,
+        "--new-key",
+        NEW_DOTENV_KEY,
+        "--in-place",
+    ]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let new_vault = dotenv_vault::Vault::from_path(tmp.path().join(".env.vault"), NEW_DOTENV_KEY);
+    assert_eq!(new_vault.to_map().unwrap(), expected_env());
+
+    let old_vault = dotenv_vault::Vault::from_path(tmp.path().join(".env.vault"), DOTENV_KEY);
+    assert!(old_vault.verify().is_err());
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_rotate_invalid_old_key() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path()).args([
+        "rotate",
+        "--old-key",
+        NEW_DOTENV_KEY,
+        "--new-key",
+        DOTENV_KEY,
+        "--in-place",
+    ]);
+
+    cmd.assert().failure();
+
+    let contents = std::fs::read_to_string(tmp.path().join(".env.vault")).unwrap();
+    assert!(contents.contains(ESCAPES_CIPHERTEXT));
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_encrypt() {
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.args(["encrypt", "--key", DOTENV_KEY, "--value", "ALPHA=\"zeta\""]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let entry = String::from_utf8(output.stdout).unwrap();
+    assert!(entry.starts_with("DOTENV_VAULT_PRODUCTION=\""));
+
+    let vault = dotenv_vault::Vault::from_bytes(entry, DOTENV_KEY);
+    assert_eq!(vault.get("ALPHA").unwrap().unwrap(), "zeta");
+}
+
+#[test]
+fn dotenv_vault_cli_encrypt_passphrase_key() {
+    // A 64 hex character passphrase must not be used as a raw key
+    let passphrase = "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00";
+    for key in [
+        "dotenv://:correct%20horse@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1000".to_string(),
+        format!("dotenv://:{}@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1000", passphrase),
+    ] {
+        let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+        cmd.args(["encrypt", "--key", &key, "--value", "ALPHA=\"zeta\""]);
+
+        let output = cmd.output().unwrap();
+        assert!(output.status.success());
+        let entry = String::from_utf8(output.stdout).unwrap();
+
+        let vault = dotenv_vault::Vault::from_bytes(entry, &key);
+        assert_eq!(vault.get("ALPHA").unwrap().unwrap(), "zeta");
+    }
+}
+
+#[test]
+fn dotenv_vault_cli_rotate_to_passphrase_key() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+    let new_key = "dotenv://:ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production&kdf=pbkdf2&iterations=1000";
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path())
+        .args(["rotate", "--old-key", DOTENV_KEY, "--new-key", new_key]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let rotated = String::from_utf8(output.stdout).unwrap();
+
+    let vault = dotenv_vault::Vault::from_bytes(rotated, new_key);
+    assert_eq!(vault.to_map().unwrap(), expected_env());
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_encrypt_append_to() {
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let staging_key = "dotenv://:key_01b08fe1173b781cce5fd1a18178c5cacdf3bb0845a8aa1b8089ac0751f7ed9c@dotenv.local/vault/.env.vault?environment=staging";
+
+    // An existing entry without a trailing newline is kept intact
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.args([
+        "encrypt",
+        "--key",
+        staging_key,
+        "--value",
+        "ALPHA=\"from staging\"",
+        "--append-to",
+    ])
+    .arg(&vault_path);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", staging_key)
+        .current_dir(tmp.path())
+        .args(["run", "--print-env", "--format", "json"]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let parsed: HashMap<String, String> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        parsed,
+        HashMap::from([("ALPHA".to_string(), "from staging".to_string())])
+    );
+
+    let production = dotenv_vault::Vault::from_path(&vault_path, DOTENV_KEY);
+    assert_eq!(production.to_map().unwrap(), expected_env());
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_log_level_none() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .env("DOTENV_VAULT_LOG_LEVEL", "none")
+        .current_dir(tmp.path())
+        .args(["run", "--print-env"]);
+
+    cmd.assert().success().stderr("");
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_log_level_info() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .env("DOTENV_VAULT_LOG_LEVEL", "info")
+        .current_dir(tmp.path())
+        .args(["run", "--print-env"]);
+
+    cmd.assert().success();
+    let output = cmd.output().unwrap();
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Loading env from encrypted .env.vault"));
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_quiet() {
+    let tmp = tempdir().unwrap();
+    File::create(tmp.path().join(".env")).unwrap();
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .env_remove("DOTENV_VAULT_LOG_LEVEL")
+        .current_dir(tmp.path())
+        .args(["run", "--print-env"]);
+    let output = cmd.output().unwrap();
+    assert!(String::from_utf8(output.stderr).unwrap().contains(&format!(
+        "[dotenv-vault@{}][WARN]",
+        env!("CARGO_PKG_VERSION")
+    )));
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .env_remove("DOTENV_VAULT_LOG_LEVEL")
+        .current_dir(tmp.path())
+        .args(["--quiet", "run", "--print-env"]);
+
+    cmd.assert().success().stderr("");
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_verbose() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .env("DOTENV_VAULT_LOG_LEVEL", "none")
+        .current_dir(tmp.path())
+        .args(["--verbose", "run", "--print-env"]);
+
+    cmd.assert().success();
+    let output = cmd.output().unwrap();
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Loading env from encrypted .env.vault"));
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_log_level_conflict() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .current_dir(tmp.path())
+        .args(["--quiet", "--log-level", "info", "run", "--print-env"]);
+
+    cmd.assert().failure().code(14);
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_exit_codes() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    // CLI failures are kept apart from the exit codes of library errors
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.args(["run", "--cwd"])
+        .arg(tmp.path().join("missing"))
+        .args(["--", "true"]);
+    cmd.assert().failure().code(11);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.env("DOTENV_KEY", DOTENV_KEY)
+        .current_dir(tmp.path())
+        .args(["run", "--", "dotenv-vault-missing-program"]);
+    cmd.assert().failure().code(10);
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_diff() {
+    let tmp = tempdir().unwrap();
+    write_staging_vault(tmp.path());
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path())
+        .args(["diff", DOTENV_KEY, STAGING_DOTENV_KEY]);
+
+    cmd.assert().success().stdout(concat!(
+        "~ALPHA=\"zeta\" -> \"from staging\"\n",
+        "+EXTRA=\"only staging\"\n",
+        "-MULTI=\"line one\\nline two\"\n",
+        "-QUOTED=\"he said \\\"hi\\\" to \\$USER\"\n",
+    ));
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_diff_keys_only() {
+    let tmp = tempdir().unwrap();
+    write_staging_vault(tmp.path());
+    std::fs::rename(
+        tmp.path().join(".env.vault"),
+        tmp.path().join("staging.vault"),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path()).args([
+        "diff",
+        DOTENV_KEY,
+        STAGING_DOTENV_KEY,
+        "--file",
+        "staging.vault",
+        "--keys-only",
+    ]);
+
+    cmd.assert()
+        .success()
+        .stdout("~ALPHA\n+EXTRA\n-MULTI\n-QUOTED\n");
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_diff_same_key() {
+    let tmp = tempdir().unwrap();
+    write_staging_vault(tmp.path());
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path())
+        .args(["diff", DOTENV_KEY, DOTENV_KEY]);
+
+    cmd.assert().success().stdout("");
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_init() {
+    let tmp = tempdir().unwrap();
+    std::fs::write(tmp.path().join(".env"), "ALPHA=\"zeta\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.args(["init", "--cwd"]).arg(tmp.path());
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let dotenv_key = String::from_utf8(output.stdout).unwrap();
+    assert!(dotenv_key.starts_with("dotenv://:key_"));
+    assert!(dotenv_key.ends_with("?environment=development\n"));
+
+    let vault = dotenv_vault::Vault::from_path(tmp.path().join(".env.vault"), dotenv_key.trim());
+    assert_eq!(
+        vault.to_map().unwrap(),
+        HashMap::from([("ALPHA".to_string(), "zeta".to_string())])
+    );
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_init_existing_vault() {
+    let tmp = tempdir().unwrap();
+    write_vault(tmp.path(), ESCAPES_CIPHERTEXT);
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path()).arg("init");
+
+    cmd.assert().failure().stdout("");
+    let contents = std::fs::read_to_string(tmp.path().join(".env.vault")).unwrap();
+    assert!(contents.contains(ESCAPES_CIPHERTEXT));
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path()).args(["init", "--force"]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let dotenv_key = String::from_utf8(output.stdout).unwrap();
+
+    let vault = dotenv_vault::Vault::from_path(tmp.path().join(".env.vault"), dotenv_key.trim());
+    assert_eq!(vault.verify().unwrap(), ("development".to_string(), 0));
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_generate_key() {
+    let tmp = tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.arg("generate-key");
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let dotenv_key = String::from_utf8(output.stdout).unwrap();
+    let dotenv_key = dotenv_key.trim();
+
+    let url = url::Url::parse(dotenv_key).unwrap();
+    assert_eq!(url.scheme(), "dotenv");
+    assert_eq!(url.query(), Some("environment=production"));
+    let (key, entry) = dotenv_vault::Vault::from_key(dotenv_key)
+        .instructions(dotenv_key)
+        .unwrap();
+    assert!(key.starts_with("key_"));
+    assert_eq!(entry, "DOTENV_VAULT_PRODUCTION");
+
+    // The generated key can encrypt a new vault that validates
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path())
+        .args(["encrypt", "--key", dotenv_key, "--value", "ALPHA=\"zeta\""])
+        .args(["--append-to", ".env.vault"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.current_dir(tmp.path())
+        .args(["validate", "--dotenv-key", dotenv_key]);
+    cmd.assert().success();
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_generate_key_json_save_to() {
+    let tmp = tempdir().unwrap();
+    let keys_path = tmp.path().join(".env.keys");
+    std::fs::write(&keys_path, "DOTENV_KEY_DEVELOPMENT=\"existing\"").unwrap();
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.args([
+        "generate-key",
+        "--environment",
+        "staging",
+        "--format",
+        "json",
+    ])
+    .arg("--save-to")
+    .arg(&keys_path);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["environment"], "staging");
+    let dotenv_key = json["dotenv_key"].as_str().unwrap();
+    assert!(dotenv_key.ends_with("?environment=staging"));
+
+    let keys = std::fs::read_to_string(&keys_path).unwrap();
+    assert_eq!(
+        keys,
+        format!(
+            "DOTENV_KEY_DEVELOPMENT=\"existing\"\nDOTENV_KEY_STAGING=\"{}\"\n",
+            dotenv_key
+        )
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(&keys_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.args(["generate-key", "review", "--environment", "staging"]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .ends_with("?environment=review\n"));
+
+    // A new .env.keys file is created only readable by the current user
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let new_keys_path = tmp.path().join("new.env.keys");
+        let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+        cmd.arg("generate-key").arg("--save-to").arg(&new_keys_path);
+        cmd.assert().success();
+
+        let mode = std::fs::metadata(&new_keys_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    tmp.close().unwrap();
+}
+
+#[test]
+fn dotenv_vault_cli_version() {
+    let mut cmd = Command::cargo_bin("dotenv-vault").unwrap();
+    cmd.arg("version");
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+
+    let version = lines
+        .next()
+        .and_then(|line| line.strip_prefix("dotenv-vault "))
+        .unwrap();
+    assert_eq!(version, env!("CARGO_PKG_VERSION"));
+    let parts: Vec<&str> = version.split('.').collect();
+    assert_eq!(parts.len(), 3);
+    assert!(parts.iter().all(|part| part.parse::<u64>().is_ok()));
+
+    assert!(lines.next().unwrap().starts_with("commit: "));
+    assert!(lines.next().unwrap().starts_with("build date: "));
+    assert!(lines.next().unwrap().starts_with("rustc: rustc "));
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "compress")]
+
+use dotenv_vault::{Error, Vault};
+use std::collections::HashMap;
+
+const KEY: &str = "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00";
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production&compress=zstd";
+
+#[test]
+fn compress_round_trip() {
+    let plaintext = (0..100)
+        .map(|i| format!("SECRET_{}=\"{}\"\n", i, "x".repeat(64)))
+        .collect::<String>();
+
+    let compressed = Vault::encrypt(plaintext.as_bytes(), KEY, true).unwrap();
+    let uncompressed = Vault::encrypt(plaintext.as_bytes(), KEY, false).unwrap();
+    assert!(compressed.len() < uncompressed.len());
+
+    let vault = Vault::from_bytes(
+        format!("DOTENV_VAULT_PRODUCTION=\"{}\"", compressed),
+        DOTENV_KEY,
+    );
+    let (_, decrypted) = vault.decrypt_entry().unwrap();
+    assert_eq!(decrypted, plaintext.as_bytes());
+
+    let map = vault.to_map().unwrap();
+    assert_eq!(map.len(), 100);
+    assert_eq!(map["SECRET_42"], "x".repeat(64));
+}
+
+#[test]
+fn compress_param() {
+    assert!(Vault::uses_compression(DOTENV_KEY).unwrap());
+    assert!(!Vault::uses_compression(
+        "dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=production"
+    )
+    .unwrap());
+}
+
+#[test]
+fn compress_uncompressed_entry() {
+    let ciphertext = Vault::encrypt(b"ALPHA=\"zeta\"", KEY, false).unwrap();
+    let vault = Vault::from_bytes(
+        format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext),
+        DOTENV_KEY,
+    );
+
+    assert!(matches!(vault.to_map(), Err(Error::IoError(_))));
+    assert_eq!(
+        Vault::from_bytes(
+            format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext),
+            DOTENV_KEY.trim_end_matches("&compress=zstd"),
+        )
+        .to_map()
+        .unwrap(),
+        HashMap::from([("ALPHA".to_string(), "zeta".to_string())])
+    );
+}
+
+#[test]
+fn compress_load_environment() {
+    std::env::remove_var("COMPRESS_ALPHA");
+
+    let ciphertext = Vault::encrypt(b"COMPRESS_ALPHA=\"omega\"", KEY, true).unwrap();
+    let vault = Vault::from_bytes(
+        format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext),
+        DOTENV_KEY,
+    );
+
+    let result = vault.load_environment("production");
+    let alpha = std::env::var("COMPRESS_ALPHA");
+    std::env::remove_var("COMPRESS_ALPHA");
+
+    assert!(result.is_ok());
+    assert_eq!(alpha.unwrap(), "omega");
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "config-rs")]
+
+use config::Config;
+use dotenv_vault::VaultSource;
+use serial_test::serial;
+use std::{env, fs::File, io::prelude::*};
+use tempfile::tempdir;
+
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn config_vault_source() {
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(vault_path).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    let cwd = env::current_dir().unwrap();
+    env::set_current_dir(&tmp).unwrap();
+
+    let config = Config::builder()
+        .add_source(VaultSource::from_key(DOTENV_KEY))
+        .build()
+        .unwrap();
+    assert_eq!(config.get_string("alpha").unwrap(), "zeta");
+    assert!(env::var("ALPHA").is_err());
+
+    tmp.close().unwrap();
+    env::set_current_dir(cwd).unwrap();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn config_vault_source_invalid_key() {
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(vault_path).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"XXNYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    let cwd = env::current_dir().unwrap();
+    env::set_current_dir(&tmp).unwrap();
+
+    let config = Config::builder()
+        .add_source(VaultSource::from_key(DOTENV_KEY))
+        .build();
+    assert!(config.is_err());
+
+    tmp.close().unwrap();
+    env::set_current_dir(cwd).unwrap();
+}
@@ -0,0 +1,52 @@
+use dotenv_vault::{Error, VaultBuilder, VaultConfig};
+use serial_test::serial;
+use std::{env, fs};
+use tempfile::tempdir;
+
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn with_env_var_name_ok() {
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    fs::write(&vault_path, "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"").unwrap();
+
+    env::remove_var("DOTENV_KEY");
+    env::set_var("APP_DOTENV_KEY", DOTENV_KEY);
+
+    let env_map = VaultBuilder::new()
+        .with_env_var_name("APP_DOTENV_KEY")
+        .with_path(&vault_path)
+        .build()
+        .into_env_map()
+        .unwrap();
+    assert_eq!(env_map.get("ALPHA").map(String::as_str), Some("zeta"));
+
+    env::remove_var("APP_DOTENV_KEY");
+    tmp.close().unwrap();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn with_env_var_name_ignores_dotenv_key() {
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    fs::write(&vault_path, "DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"").unwrap();
+
+    env::set_var("DOTENV_KEY", DOTENV_KEY);
+    env::set_var("APP_DOTENV_KEY", "dotenv://:key_0000000000000000000000000000000000000000000000000000000000000000@dotenv.local/vault/.env.vault?environment=production");
+
+    let mut config = VaultConfig::default();
+    config.key_var_name = "APP_DOTENV_KEY".to_string();
+    let result = VaultBuilder::new()
+        .with_config(config)
+        .with_path(&vault_path)
+        .build()
+        .into_env_map();
+    assert!(matches!(result, Err(Error::InvalidKey)));
+
+    env::remove_var("DOTENV_KEY");
+    env::remove_var("APP_DOTENV_KEY");
+    tmp.close().unwrap();
+}
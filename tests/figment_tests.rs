@@ -0,0 +1,80 @@
+#![cfg(feature = "figment")]
+
+use dotenv_vault::VaultProvider;
+use figment::Figment;
+use serial_test::serial;
+use std::{env, fs::File, io::prelude::*};
+use tempfile::tempdir;
+
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+#[derive(serde::Deserialize)]
+struct Config {
+    alpha: String,
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn figment_vault_provider() {
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(vault_path).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    let cwd = env::current_dir().unwrap();
+    env::set_current_dir(&tmp).unwrap();
+
+    let config: Config = Figment::from(VaultProvider::from_key(DOTENV_KEY))
+        .extract()
+        .unwrap();
+    assert_eq!(config.alpha, "zeta");
+    assert!(env::var("ALPHA").is_err());
+
+    tmp.close().unwrap();
+    env::set_current_dir(cwd).unwrap();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn figment_vault_provider_fallback_to_env() {
+    let tmp = tempdir().unwrap();
+    let env_path = tmp.path().join(".env");
+    let mut env_file = File::create(env_path).unwrap();
+    env_file
+        .write_all("ALPHA=\"from .env\"".as_bytes())
+        .unwrap();
+    env_file.sync_all().unwrap();
+
+    let cwd = env::current_dir().unwrap();
+    env::set_current_dir(&tmp).unwrap();
+
+    let config: Config = Figment::from(VaultProvider::default()).extract().unwrap();
+    assert_eq!(config.alpha, "from .env");
+
+    tmp.close().unwrap();
+    env::set_current_dir(cwd).unwrap();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn figment_vault_provider_invalid_key() {
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(vault_path).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"XXNYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    let cwd = env::current_dir().unwrap();
+    env::set_current_dir(&tmp).unwrap();
+
+    let config = Figment::from(VaultProvider::from_key(DOTENV_KEY)).extract::<Config>();
+    assert!(config.is_err());
+
+    tmp.close().unwrap();
+    env::set_current_dir(cwd).unwrap();
+}
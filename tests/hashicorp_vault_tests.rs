@@ -0,0 +1,134 @@
+#![cfg(feature = "hashicorp-vault")]
+
+use dotenv_vault::{VaultBuilder, VaultConfig};
+use serial_test::serial;
+use std::{env, fs::File, io::prelude::*};
+use tempfile::tempdir;
+
+fn hashicorp_config(addr: String) -> VaultConfig {
+    let mut config = VaultConfig::default();
+    config.hc_vault_addr = Some(addr);
+    config.hc_vault_token = Some("hvs.test-token".to_string());
+    config.hc_vault_path = Some("secret/data/my-app".to_string());
+    config
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn hashicorp_vault_key() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("GET", "/v1/secret/data/my-app")
+        .match_header("x-vault-token", "hvs.test-token")
+        .with_header("content-type", "application/json")
+        .with_body(r#"{
+            "data": {
+                "data": {
+                    "DOTENV_KEY": "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production"
+                },
+                "metadata": { "version": 1 }
+            }
+        }"#)
+        .create();
+
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(&vault_path).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    let vault = VaultBuilder::new()
+        .with_config(hashicorp_config(server.url()))
+        .with_path(&vault_path)
+        .build();
+    let map = vault.to_map().unwrap();
+
+    mock.assert();
+    assert_eq!(map.get("ALPHA").map(String::as_str), Some("omega"));
+    assert_eq!(map.get("BETA").map(String::as_str), Some("gamma"));
+
+    tmp.close().unwrap();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn hashicorp_vault_unavailable() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("GET", "/v1/secret/data/my-app")
+        .with_status(403)
+        .create();
+
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    File::create(&vault_path).unwrap();
+
+    let vault = VaultBuilder::new()
+        .with_config(hashicorp_config(server.url()))
+        .with_path(&vault_path)
+        .with_logging_disabled()
+        .build();
+
+    mock.assert();
+    assert!(matches!(
+        vault.verify(),
+        Err(dotenv_vault::Error::KeyNotFound)
+    ));
+
+    tmp.close().unwrap();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn hashicorp_vault_env_key_wins() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("GET", "/v1/secret/data/my-app")
+        .expect(0)
+        .create();
+
+    let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+    env::set_var("DOTENV_KEY", key);
+
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(&vault_path).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    let vault = VaultBuilder::new()
+        .with_config(hashicorp_config(server.url()))
+        .with_path(&vault_path)
+        .build();
+    let map = vault.to_map();
+    env::remove_var("DOTENV_KEY");
+
+    mock.assert();
+    assert_eq!(map.unwrap().get("ALPHA").map(String::as_str), Some("omega"));
+
+    tmp.close().unwrap();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn hashicorp_vault_given_key_skips_fetch() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("GET", "/v1/secret/data/my-app")
+        .expect(0)
+        .create();
+
+    env::remove_var("DOTENV_KEY");
+    let key = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+    let vault = VaultBuilder::new()
+        .with_config(hashicorp_config(server.url()))
+        .with_key(key)
+        .build();
+
+    mock.assert();
+    assert_eq!(vault.format_version().unwrap(), 1);
+}
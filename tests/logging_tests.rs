@@ -0,0 +1,66 @@
+use dotenv_vault::{Vault, VaultBuilder};
+use std::{env, fs::File, io::prelude::*, process::Command};
+use tempfile::tempdir;
+
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+/// Run `test` in a child test process and return what it wrote to stderr
+fn child_stderr(test: &str) -> String {
+    let output = Command::new(env::current_exe().unwrap())
+        .args([test, "--exact", "--nocapture", "--test-threads=1"])
+        .env("DOTENV_VAULT_LOGGING_CHILD", "1")
+        .env_remove("DOTENV_VAULT_LOG_LEVEL")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    String::from_utf8(output.stderr).unwrap()
+}
+
+/// Load a vault that is missing its *.env.vault* file, which logs a warning
+fn load_missing_vault(vault: impl FnOnce(&std::path::Path) -> Vault) {
+    let tmp = tempdir().unwrap();
+    let mut env_file = File::create(tmp.path().join(".env")).unwrap();
+    env_file.write_all(b"LOGGING_ALPHA=zeta").unwrap();
+    env_file.sync_all().unwrap();
+
+    env::set_current_dir(tmp.path()).unwrap();
+    vault(tmp.path()).load().unwrap();
+    assert_eq!(env::var("LOGGING_ALPHA").unwrap(), "zeta");
+}
+
+#[test]
+fn logging_enabled_child() {
+    if env::var("DOTENV_VAULT_LOGGING_CHILD").is_err() {
+        return;
+    }
+
+    load_missing_vault(|path| Vault::from_path(path.join(".env.vault"), DOTENV_KEY));
+}
+
+#[test]
+fn logging_disabled_child() {
+    if env::var("DOTENV_VAULT_LOGGING_CHILD").is_err() {
+        return;
+    }
+
+    load_missing_vault(|path| {
+        VaultBuilder::new()
+            .with_key(DOTENV_KEY)
+            .with_path(path.join(".env.vault"))
+            .with_logging_disabled()
+            .build()
+    });
+}
+
+#[test]
+fn logging_enabled() {
+    let stderr = child_stderr("logging_enabled_child");
+    assert!(stderr.contains("[WARN] You set a DOTENV_KEY but you are missing a .env.vault file"));
+}
+
+#[test]
+fn logging_disabled() {
+    let stderr = child_stderr("logging_disabled_child");
+    assert!(!stderr.contains("[dotenv-vault@"));
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "macros")]
+
+use dotenv_vault::dotenv_vault;
+use serial_test::serial;
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use tempfile::tempdir;
+
+#[dotenv_vault]
+#[derive(Debug, PartialEq)]
+struct Config {
+    #[env(key = "MACROS_DATABASE_URL", required)]
+    database_url: String,
+    #[env(key = "MACROS_PORT")]
+    port: u16,
+    macros_debug: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_url: String::new(),
+            port: 8080,
+            macros_debug: false,
+        }
+    }
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn dotenv_vault_attribute_ok() {
+    env::set_var("MACROS_DATABASE_URL", "postgres://localhost/app");
+    env::set_var("MACROS_DEBUG", "true");
+
+    let config = Config::from_dotenv_vault();
+
+    env::remove_var("MACROS_DATABASE_URL");
+    env::remove_var("MACROS_DEBUG");
+
+    assert_eq!(
+        config,
+        Config {
+            database_url: "postgres://localhost/app".to_string(),
+            port: 8080,
+            macros_debug: true,
+        }
+    );
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+#[should_panic(expected = "MACROS_DATABASE_URL not set")]
+fn dotenv_vault_attribute_required() {
+    env::remove_var("MACROS_DATABASE_URL");
+
+    Config::from_dotenv_vault();
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+#[should_panic(expected = "MACROS_PORT is not valid")]
+fn dotenv_vault_attribute_invalid() {
+    env::set_var("MACROS_DATABASE_URL", "postgres://localhost/app");
+    env::set_var("MACROS_PORT", "http");
+
+    let result = std::panic::catch_unwind(Config::from_dotenv_vault);
+
+    env::remove_var("MACROS_DATABASE_URL");
+    env::remove_var("MACROS_PORT");
+    std::panic::resume_unwind(result.unwrap_err());
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+#[should_panic(expected = "INVALID_DOTENV_KEY")]
+fn dotenv_vault_attribute_mismatched_key() {
+    let tmp = tempdir().unwrap();
+    let mut vault = File::create(tmp.path().join(".env.vault")).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    let cwd = env::current_dir().unwrap();
+    env::set_current_dir(&tmp).unwrap();
+    env::set_var("DOTENV_KEY", "dotenv://:key_0000000000000000000000000000000000000000000000000000000000000000@dotenv.local/vault/.env.vault?environment=production");
+    env::set_var("MACROS_DATABASE_URL", "postgres://localhost/app");
+
+    let result = std::panic::catch_unwind(Config::from_dotenv_vault);
+
+    env::remove_var("DOTENV_KEY");
+    env::remove_var("MACROS_DATABASE_URL");
+    env::set_current_dir(cwd).unwrap();
+    tmp.close().unwrap();
+    std::panic::resume_unwind(result.unwrap_err());
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "metrics")]
+
+use dotenv_vault::Vault;
+use metrics_util::{
+    debugging::{DebugValue, DebuggingRecorder},
+    MetricKind,
+};
+use std::{fs::File, io::prelude::*};
+use tempfile::tempdir;
+
+#[test]
+fn load_counts_attempts() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(&vault_path).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    metrics::with_local_recorder(&recorder, || {
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+        assert!(vault.load().is_ok());
+
+        let vault = Vault::from_path(&vault_path, "dotenv://:key_0000000000000000000000000000000000000000000000000000000000000000@dotenv.local/vault/.env.vault?environment=production");
+        assert!(vault.load().is_err());
+    });
+
+    let counters: Vec<(String, Vec<String>, u64)> = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .filter_map(|(key, _, _, value)| match (key.kind(), value) {
+            (MetricKind::Counter, DebugValue::Counter(count)) => Some((
+                key.key().name().to_string(),
+                key.key()
+                    .labels()
+                    .map(|label| format!("{}={}", label.key(), label.value()))
+                    .collect(),
+                count,
+            )),
+            _ => None,
+        })
+        .collect();
+
+    let count = |name: &str, labels: &[&str]| {
+        counters
+            .iter()
+            .find(|(n, l, _)| n == name && l == labels)
+            .map(|(_, _, count)| *count)
+    };
+    assert_eq!(count("dotenv_vault.load.attempts", &[]), Some(2));
+    assert_eq!(count("dotenv_vault.load.success", &[]), Some(1));
+    assert_eq!(
+        count(
+            "dotenv_vault.load.error",
+            &["error_code=INVALID_DOTENV_KEY"]
+        ),
+        Some(1)
+    );
+
+    tmp.close().unwrap();
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "opentelemetry")]
+
+use dotenv_vault::Vault;
+use opentelemetry::{global, Value};
+use opentelemetry_sdk::{testing::trace::InMemorySpanExporter, trace::TracerProvider};
+use std::{env, fs::File, io::prelude::*};
+use tempfile::tempdir;
+
+#[test]
+fn load_reports_span() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    global::set_tracer_provider(provider);
+
+    let tmp = tempdir().unwrap();
+    let vault_path = tmp.path().join(".env.vault");
+    let mut vault = File::create(&vault_path).unwrap();
+    vault
+        .write_all("DOTENV_VAULT_PRODUCTION=\"s7NYXa809k/bVSPwIAmJhPJmEGTtU0hG58hOZy7I0ix6y5HP8LsHBsZCYC/gw5DDFy5DgOcyd18R\"".as_bytes())
+        .unwrap();
+    vault.sync_all().unwrap();
+
+    let vault = Vault::from_path(&vault_path, "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production");
+    assert!(vault.load().is_ok());
+    assert_eq!(env::var("ALPHA").unwrap(), "zeta");
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 1);
+
+    let span = &spans[0];
+    assert_eq!(span.name, "dotenv_vault.load");
+
+    let attribute = |name: &str| {
+        span.attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == name)
+            .map(|attribute| attribute.value.clone())
+    };
+    assert_eq!(
+        attribute("vault.path"),
+        Some(Value::from(vault_path.display().to_string()))
+    );
+    assert_eq!(
+        attribute("vault.environment"),
+        Some(Value::from("production"))
+    );
+    assert_eq!(attribute("vault.success"), Some(Value::Bool(true)));
+    assert!(matches!(attribute("vault.duration_ms"), Some(Value::I64(ms)) if ms >= 0));
+
+    tmp.close().unwrap();
+}
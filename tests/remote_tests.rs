@@ -0,0 +1,76 @@
+#![cfg(feature = "remote")]
+
+use dotenv_vault::{Error, VaultBuilder, VaultConfig};
+
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+fn remote_config(url: String, token: Option<&str>) -> VaultConfig {
+    let mut config = VaultConfig::default();
+    config.vault_url = Some(url);
+    config.vault_auth_token = token.map(str::to_string);
+    config
+}
+
+#[test]
+fn remote_vault_ok() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("GET", "/.env.vault")
+        .match_header("authorization", "Bearer secret-token")
+        .with_body("DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"")
+        .create();
+
+    let vault = VaultBuilder::new()
+        .with_key(DOTENV_KEY)
+        .with_config(remote_config(
+            format!("{}/.env.vault", server.url()),
+            Some("secret-token"),
+        ))
+        .build();
+    let map = vault.to_map().unwrap();
+
+    mock.assert();
+    assert_eq!(map.get("ALPHA").map(String::as_str), Some("omega"));
+    assert_eq!(map.get("BETA").map(String::as_str), Some("gamma"));
+}
+
+#[test]
+fn remote_vault_unauthorized() {
+    let mut server = mockito::Server::new();
+    let mock = server.mock("GET", "/.env.vault").with_status(401).create();
+
+    let vault = VaultBuilder::new()
+        .with_key(DOTENV_KEY)
+        .with_config(remote_config(format!("{}/.env.vault", server.url()), None))
+        .build();
+
+    let result = vault.to_map();
+
+    mock.assert();
+    assert!(matches!(result, Err(Error::RemoteError(_))));
+}
+
+#[test]
+fn remote_vault_records_key() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("GET", "/.env.vault")
+        .with_body("DOTENV_VAULT_PRODUCTION=\"nlMfTHqzt7GIlqAhPEQIRuS2um7+OILQstDQ3OYBZ0UA+jZFoEhzEQVRyq5dXHfXmWf/LfVCggjcv3v97jbD6YXUc3KISw==\"")
+        .create();
+
+    let vault = VaultBuilder::new()
+        .with_key(&format!("{}&key_id=v2", DOTENV_KEY))
+        .with_config(remote_config(format!("{}/.env.vault", server.url()), None))
+        .build();
+    let result = vault.load();
+    std::env::remove_var("ALPHA");
+    std::env::remove_var("BETA");
+
+    mock.assert();
+    assert!(result.is_ok());
+    assert_eq!(
+        vault.last_key_fingerprint().as_deref(),
+        Some("bfeade18ad3ef8be")
+    );
+    assert_eq!(vault.last_key_id().as_deref(), Some("v2"));
+}
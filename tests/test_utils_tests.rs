@@ -0,0 +1,54 @@
+#![cfg(feature = "test-utils")]
+
+use dotenv_vault::test_utils::{
+    known_test_key, known_test_vault_entry, setup_vault_env, with_vault,
+};
+use serial_test::serial;
+use std::env;
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn setup_vault_env_restores_state() {
+    let cwd = env::current_dir().unwrap();
+    env::remove_var("ALPHA");
+    env::set_var(
+        "DOTENV_KEY",
+        "dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=production",
+    );
+
+    let vault_env = setup_vault_env(known_test_key(), &[known_test_vault_entry()]);
+    assert!(vault_env.vault_path().is_file());
+    assert_eq!(
+        env::current_dir().unwrap(),
+        vault_env.path().canonicalize().unwrap()
+    );
+    assert_eq!(env::var("DOTENV_KEY").unwrap(), known_test_key());
+
+    dotenv_vault::dotenv().unwrap();
+    assert_eq!(env::var("ALPHA").unwrap(), "omega");
+
+    let vault_path = vault_env.vault_path();
+    drop(vault_env);
+
+    assert!(!vault_path.exists());
+    assert_eq!(env::current_dir().unwrap(), cwd);
+    assert!(env::var("ALPHA").is_err());
+    assert_eq!(
+        env::var("DOTENV_KEY").unwrap(),
+        "dotenv://:key_1234@dotenv.local/vault/.env.vault?environment=production"
+    );
+
+    env::remove_var("DOTENV_KEY");
+}
+
+#[test]
+#[serial] // Run serially due to env modifications
+fn with_vault_ok() {
+    with_vault(known_test_key(), &[known_test_vault_entry()], || {
+        let map = dotenv_vault::dotenv_to_map().unwrap();
+        assert_eq!(map.get("ALPHA").map(String::as_str), Some("omega"));
+        assert_eq!(map.get("BETA").map(String::as_str), Some("gamma"));
+    });
+
+    assert!(env::var("DOTENV_KEY").is_err());
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "tokio")]
+
+use dotenv_vault::{Error, Vault};
+use std::{env, time::Duration};
+
+const DOTENV_KEY: &str = "dotenv://:key_ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00@dotenv.local/vault/.env.vault?environment=production";
+
+#[tokio::test(start_paused = true)]
+async fn load_async_with_timeout_ok() {
+    env::remove_var("TOKIO_ALPHA");
+
+    let ciphertext = Vault::encrypt(
+        b"TOKIO_ALPHA=\"omega\"",
+        "ddcaa26504cd70a6fef9801901c3981538563a1767c297cb8416e8a38c62fe00",
+        false,
+    )
+    .unwrap();
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join(".env.vault");
+    std::fs::write(&path, format!("DOTENV_VAULT_PRODUCTION=\"{}\"", ciphertext)).unwrap();
+
+    let result = Vault::from_path(&path, DOTENV_KEY)
+        .load_async_with_timeout(Duration::from_secs(5))
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(env::var("TOKIO_ALPHA").unwrap(), "omega");
+
+    env::remove_var("TOKIO_ALPHA");
+    tmp.close().unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test(start_paused = true)]
+async fn load_async_with_timeout_hung_read() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join(".env.vault");
+
+    // Opening a FIFO for reading blocks until a writer opens it, like a hung network mount
+    let status = std::process::Command::new("mkfifo")
+        .arg(&path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let vault = Vault::from_path(&path, DOTENV_KEY);
+    let (result, ()) = tokio::join!(
+        vault.load_async_with_timeout(Duration::from_secs(5)),
+        tokio::time::advance(Duration::from_secs(6)),
+    );
+
+    assert!(
+        matches!(result, Err(Error::LoadTimeout(duration)) if duration == Duration::from_secs(5))
+    );
+
+    // Let the blocked read finish so the runtime can shut down
+    drop(std::fs::OpenOptions::new().write(true).open(&path).unwrap());
+    tmp.close().unwrap();
+}